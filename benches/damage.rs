@@ -0,0 +1,134 @@
+//! Benchmarks of how changed tiles are tracked between frames: the screen
+//! buffer, with its row damage bitmap and swapped double buffer, against the
+//! previous ordered set of changed points with the whole buffer cloned every
+//! frame.
+
+use andiskaz::{
+    coord::Vec2,
+    screen::bench::Buffer,
+    string::TermGrapheme,
+    tile::Tile,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::collections::BTreeSet;
+
+/// Width of the benchmarked screen.
+const WIDTH: usize = 300;
+/// Height of the benchmarked screen.
+const HEIGHT: usize = 80;
+
+/// Changed tiles tracked as in a screen buffer.
+trait Tracker {
+    /// Creates the tracker for a blank screen.
+    fn blank() -> Self;
+
+    /// Sets the tile at the given index, recording the change.
+    fn set(&mut self, index: usize, tile: &Tile);
+
+    /// Visits the changed tiles, as when rendering, and advances to the next
+    /// frame.
+    fn frame(&mut self) -> usize;
+}
+
+/// Ordered set of changed points, with `old` cloned from `curr` every frame.
+struct PointSet {
+    old: Vec<Tile>,
+    curr: Vec<Tile>,
+    changed: BTreeSet<Vec2>,
+}
+
+impl Tracker for PointSet {
+    fn blank() -> Self {
+        let curr = vec![Tile::default(); WIDTH * HEIGHT];
+        Self { old: curr.clone(), curr, changed: BTreeSet::new() }
+    }
+
+    fn set(&mut self, index: usize, tile: &Tile) {
+        self.curr[index].clone_from(tile);
+        let point =
+            Vec2 { x: (index % WIDTH) as u16, y: (index / WIDTH) as u16 };
+        if self.old[index] != self.curr[index] {
+            self.changed.insert(point);
+        } else {
+            self.changed.remove(&point);
+        }
+    }
+
+    fn frame(&mut self) -> usize {
+        let mut visited = 0;
+        for point in self.changed.iter() {
+            let index = usize::from(point.y) * WIDTH + usize::from(point.x);
+            visited += black_box(&self.curr[index]).grapheme.width();
+        }
+        self.changed.clear();
+        self.old.clone_from(&self.curr);
+        visited
+    }
+}
+
+/// The screen buffer itself.
+struct ScreenBuffer {
+    buffer: Buffer,
+}
+
+impl Tracker for ScreenBuffer {
+    fn blank() -> Self {
+        let size = Vec2 { x: WIDTH as u16, y: HEIGHT as u16 };
+        Self { buffer: Buffer::blank(size) }
+    }
+
+    fn set(&mut self, index: usize, tile: &Tile) {
+        let point =
+            Vec2 { x: (index % WIDTH) as u16, y: (index / WIDTH) as u16 };
+        self.buffer.set(point, tile);
+    }
+
+    fn frame(&mut self) -> usize {
+        black_box(self.buffer.frame())
+    }
+}
+
+/// Tiles alternating between frames, so that every set is a change.
+fn tiles() -> [Tile; 2] {
+    let tile = |grapheme| Tile {
+        grapheme: TermGrapheme::new(grapheme).unwrap(),
+        ..Tile::default()
+    };
+    [tile("#"), tile("@")]
+}
+
+/// Benchmarks a frame setting the tiles at the given indices.
+fn bench_frames<T, I>(criterion: &mut Criterion, name: &str, indices: I)
+where
+    T: Tracker,
+    I: Iterator<Item = usize> + Clone,
+{
+    let tiles = tiles();
+    let mut tracker = T::blank();
+    let mut parity = 0;
+    criterion.bench_function(name, |bencher| {
+        bencher.iter(|| {
+            parity ^= 1;
+            for index in indices.clone() {
+                tracker.set(index, &tiles[parity]);
+            }
+            tracker.frame()
+        })
+    });
+}
+
+fn full_update(criterion: &mut Criterion) {
+    let indices = 0 .. WIDTH * HEIGHT;
+    bench_frames::<PointSet, _>(criterion, "full/point set", indices.clone());
+    bench_frames::<ScreenBuffer, _>(criterion, "full/screen buffer", indices);
+}
+
+fn sparse_update(criterion: &mut Criterion) {
+    // A few scattered tiles, e.g. a moving sprite and a status line.
+    let indices = (0 .. WIDTH * HEIGHT).step_by(97);
+    bench_frames::<PointSet, _>(criterion, "sparse/point set", indices.clone());
+    bench_frames::<ScreenBuffer, _>(criterion, "sparse/screen buffer", indices);
+}
+
+criterion_group!(benches, full_update, sparse_update);
+criterion_main!(benches);
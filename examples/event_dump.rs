@@ -2,27 +2,28 @@
 
 use andiskaz::{
     color::{BasicColor, Color2},
-    emergency_restore,
     error::Error,
     event::{Event, Key, KeyEvent},
     style::Style,
+    terminal,
     terminal::Terminal,
     tstring,
 };
-use std::{panic, process::exit};
+use std::process::exit;
 
 /// Asynchronous main of a tokio project.
 #[tokio::main]
 async fn main() {
-    // Sets panic hook so we can see the panic even if terminal was being used
-    // in raw mode.
-    panic::set_hook(Box::new(|info| {
-        let _ = emergency_restore();
-        eprintln!("{}", info);
-    }));
-
-    // Creates a terminal with default settings and runs it.
-    let result = Terminal::run(term_main).await;
+    // Creates a terminal with mouse capture, bracketed paste and focus
+    // reporting enabled, plus a panic hook so we can see the panic even if
+    // terminal was being used in raw mode, and runs it.
+    let result = terminal::Builder::new()
+        .mouse_capture(true)
+        .bracketed_paste(true)
+        .focus_reporting(true)
+        .panic_hook(true)
+        .run(term_main)
+        .await;
     // If error, prints it out and exits with bad code.
     if let Ok(Err(error)) | Err(error) = result {
         eprintln!("{}", error);
@@ -39,7 +40,7 @@ async fn term_main(mut term: Terminal) -> Result<(), Error> {
     // Style for the event string.
     let evt_style = Style::with_colors(Color2::default()).top_margin(2);
     // Initial rendering.
-    term.lock_now().await?.screen().styled_text(&message, msg_style);
+    term.lock_now().await?.screen().styled_text(&message, msg_style.clone());
 
     loop {
         // Listens for an event, and when it happens, returns a terminal guard,
@@ -54,13 +55,14 @@ async fn term_main(mut term: Terminal) -> Result<(), Error> {
                 // If resized, message needs to be reprinted.
                 Event::Resize(_) => {
                     session.screen().clear(BasicColor::Black.into());
-                    session.screen().styled_text(&message, msg_style);
+                    session.screen().styled_text(&message, msg_style.clone());
                 },
                 _ => (),
             }
 
             // Finally, dump this event.
-            session.screen().styled_text(&tstring!["{:?}", event], evt_style);
+            let evt_string = tstring!["{:?}", event];
+            session.screen().styled_text(&evt_string, evt_style.clone());
         }
     }
 
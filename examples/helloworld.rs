@@ -1,26 +1,19 @@
 use andiskaz::{
     color::Color2,
-    emergency_restore,
     error::Error,
     event::Event,
     style::Style,
-    terminal::Terminal,
+    terminal::{Builder, Terminal},
     tstring,
 };
-use std::{panic, process::exit};
+use std::process::exit;
 
 /// Asynchronous main of a tokio project.
 #[tokio::main]
 async fn main() {
-    // Sets panic hook so we can see the panic even if terminal was being used
-    // in raw mode.
-    panic::set_hook(Box::new(|info| {
-        let _ = emergency_restore();
-        eprintln!("{}", info);
-    }));
-
-    // Creates a terminal with default settings and runs it.
-    let result = Terminal::run(term_main).await;
+    // Creates a terminal with default settings, plus a panic hook so we can
+    // see the panic even if terminal was being used in raw mode, and runs it.
+    let result = Builder::new().panic_hook(true).run(term_main).await;
     // If error, prints it out and exits with bad code.
     if let Ok(Err(error)) | Err(error) = result {
         eprintln!("{}", error);
@@ -35,7 +28,7 @@ async fn term_main(mut term: Terminal) -> Result<(), Error> {
     // Style for the string.
     let style = Style::with_colors(Color2::default());
     // Initial rendering.
-    term.lock_now().await?.screen().styled_text(&string, style);
+    term.lock_now().await?.screen().styled_text(&string, style.clone());
 
     loop {
         // Awaits for an event (key pressed, screen resized, etc).
@@ -48,10 +41,11 @@ async fn term_main(mut term: Terminal) -> Result<(), Error> {
             // User resized screen? Then the whole screen was thrown out,
             // re-rendering is required.
             Some(Event::Resize(_)) => {
-                session.screen().styled_text(&string, style);
+                session.screen().styled_text(&string, style.clone());
             },
-            // Won't really happen since we waited for an event.
-            None => (),
+            // Other events are not relevant here. `None` won't really happen
+            // since we waited for an event.
+            _ => (),
         }
     }
 
@@ -1,24 +1,17 @@
 use andiskaz::{
-    emergency_restore,
     error::Error,
-    terminal::Terminal,
+    terminal::{Builder, Terminal},
     tstring,
     ui::info::InfoDialog,
 };
-use std::{panic, process::exit};
+use std::process::exit;
 
 /// Asynchronous main of a tokio project.
 #[tokio::main]
 async fn main() {
-    // Sets panic hook so we can see the panic even if terminal was being used
-    // in raw mode.
-    panic::set_hook(Box::new(|info| {
-        let _ = emergency_restore();
-        eprintln!("{}", info);
-    }));
-
-    // Creates a terminal with default settings and runs it.
-    let result = Terminal::run(term_main).await;
+    // Creates a terminal with default settings, plus a panic hook so we can
+    // see the panic even if terminal was being used in raw mode, and runs it.
+    let result = Builder::new().panic_hook(true).run(term_main).await;
     // If error, prints it out and exits with bad code.
     if let Ok(Err(error)) | Err(error) = result {
         eprintln!("{}", error);
@@ -1,29 +1,20 @@
 use andiskaz::{
-    color::{BasicColor, Color, Color2},
+    color::{BasicColor, Color2},
     coord::{Coord, Vec2},
-    emergency_restore,
     error::Error,
     event::{Event, Key, KeyEvent, ResizeEvent},
-    screen::Screen,
-    string::{TermGrapheme, TermString},
+    screen::{CursorShape, Screen},
+    string::TermString,
     style::Style,
     terminal,
     terminal::Terminal,
-    tile::Tile,
     tstring,
 };
-use std::{panic, process::exit};
+use std::process::exit;
 
 /// Asynchronous main of a tokio project.
 #[tokio::main]
 async fn main() {
-    // Sets panic hook so we can see the panic even if terminal was being used
-    // in raw mode.
-    panic::set_hook(Box::new(|info| {
-        let _ = emergency_restore();
-        eprintln!("{}", info);
-    }));
-
     // Initializes game state.
     let game = Game::new();
     // Let's say minimum width is the width of the message.
@@ -36,6 +27,9 @@ async fn main() {
     let result = terminal::Builder::new()
         // Sets the minimum screen size.
         .min_screen(min_screen)
+        // Restores the terminal and prints the panic on panic, even if
+        // terminal was being used in raw mode.
+        .panic_hook(true)
         // Finishes the builder and runs our terminal main.
         .run(|term| term_main(game, term))
         // Awaits for the terminal main future.
@@ -71,6 +65,9 @@ async fn term_main(mut game: Game, mut term: Terminal) -> Result<(), Error> {
                 game.handle_resize(evt, session.screen()).await?
             },
 
+            // Other events are not relevant for this example.
+            Some(_) => (),
+
             // Only possible error is if the event listener failed. In this
             // case, Terminal::run or Builder::run will already tell us that
             // this fail happened and we will handle it in main.
@@ -117,43 +114,19 @@ impl Game {
         // Puts our message.
         screen.styled_text(&self.message, style);
 
-        // Renders the cursor with black foreground, white foreground.
-        self.render_cursor(screen, BasicColor::White.into()).await;
+        // Shows the terminal's cursor as a steady block.
+        screen.set_cursor_shape(CursorShape::Block, false);
+        screen.set_cursor(Some(self.cursor));
 
         Ok(())
     }
 
-    /// Renders the cursor with the given color.
-    async fn render_cursor<'terminal>(
-        &self,
-        screen: &mut Screen<'terminal>,
-        color: Color,
-    ) {
-        // A space with the given colors.
-        let tile = Tile {
-            grapheme: TermGrapheme::space(),
-            colors: Color2 {
-                // Dummy foreground color here. Ignored since the grapheme is a
-                // blank space.
-                foreground: BasicColor::White.into(),
-                // Actually used color.
-                background: color,
-            },
-        };
-        // Sets the cursor's character and colors.
-        screen.set(self.cursor, tile);
-    }
-
     /// Handles a key event.
     async fn handle_key<'terminal>(
         &mut self,
         key: KeyEvent,
         screen: &mut Screen<'terminal>,
     ) -> Result<bool, Error> {
-        // "Erases" the cursor using the same color as the background color
-        // of all the terminal.
-        self.render_cursor(screen, BasicColor::Black.into()).await;
-
         // Whether the "game" should keep executing.
         let mut executing = true;
 
@@ -197,8 +170,8 @@ impl Game {
             }
         };
 
-        // Renders the new cursor.
-        self.render_cursor(screen, BasicColor::White.into()).await;
+        // Moves the terminal's cursor.
+        screen.set_cursor(Some(self.cursor));
 
         Ok(executing)
     }
@@ -10,7 +10,7 @@ use andiskaz::{
     string::{TermGrapheme, TermString},
     style::Style,
     terminal::Terminal,
-    tile::Tile,
+    tile::{Attributes, Tile},
     tstring,
 };
 use gardiz::{direc::Direction, rect::Rect};
@@ -33,6 +33,8 @@ pub enum EndKind {
 enum State {
     /// Game running normally.
     Running,
+    /// Game paused because the terminal lost focus.
+    Paused,
     /// Screen has been put to a bad size.
     BadScreen,
     /// The game ended.
@@ -56,6 +58,8 @@ pub struct Game {
     corner_tile: Tile,
     /// Message displayed above the border.
     message: TermString,
+    /// Message displayed above the border while paused.
+    paused_message: TermString,
     /// Game state, initially "running".
     state: State,
 }
@@ -71,7 +75,8 @@ impl Game {
             foreground: BasicColor::Black.into(),
             background: BasicColor::White.into(),
         };
-        let body_tile = Tile { grapheme, colors };
+        let attributes = Attributes::NONE;
+        let body_tile = Tile { grapheme, colors, attributes, link: None };
 
         // Tile for the snake's head.
         let grapheme = TermGrapheme::new_lossy("@");
@@ -79,7 +84,8 @@ impl Game {
             foreground: BasicColor::Black.into(),
             background: BasicColor::White.into(),
         };
-        let head_tile = Tile { grapheme, colors };
+        let attributes = Attributes::BOLD;
+        let head_tile = Tile { grapheme, colors, attributes, link: None };
 
         // Tile for the food/fruit.
         let grapheme = TermGrapheme::new_lossy("ɔ́");
@@ -87,7 +93,8 @@ impl Game {
             foreground: BasicColor::Black.into(),
             background: BasicColor::LightRed.into(),
         };
-        let food_tile = Tile { grapheme, colors };
+        let attributes = Attributes::NONE;
+        let food_tile = Tile { grapheme, colors, attributes, link: None };
 
         // Tile for the vertical component of the border.
         let grapheme = TermGrapheme::new_lossy("|");
@@ -95,7 +102,8 @@ impl Game {
             foreground: BasicColor::Black.into(),
             background: BasicColor::LightGreen.into(),
         };
-        let vertical_tile = Tile { grapheme, colors };
+        let attributes = Attributes::NONE;
+        let vertical_tile = Tile { grapheme, colors, attributes, link: None };
 
         // Tile for the horizontal component of the border.
         let grapheme = TermGrapheme::new_lossy("—");
@@ -103,7 +111,8 @@ impl Game {
             foreground: BasicColor::Black.into(),
             background: BasicColor::LightGreen.into(),
         };
-        let horizontal_tile = Tile { grapheme, colors };
+        let attributes = Attributes::NONE;
+        let horizontal_tile = Tile { grapheme, colors, attributes, link: None };
 
         // Tile for the corner component of the border.
         let grapheme = TermGrapheme::new_lossy("+");
@@ -111,10 +120,13 @@ impl Game {
             foreground: BasicColor::Black.into(),
             background: BasicColor::LightGreen.into(),
         };
-        let corner_tile = Tile { grapheme, colors };
+        let attributes = Attributes::NONE;
+        let corner_tile = Tile { grapheme, colors, attributes, link: None };
 
         // Message shown above the border.
         let message = tstring!["ESC to exit, arrows to move"];
+        // Message shown above the border while the game is paused.
+        let paused_message = tstring!["Paused, focus the terminal to resume"];
 
         // Bounds of the plane (where the snake is allowed to go to).
         let bounds = Self::make_bounds(screen.size());
@@ -131,6 +143,7 @@ impl Game {
             vertical_tile,
             horizontal_tile,
             message,
+            paused_message,
             state: State::Running,
         })
     }
@@ -156,6 +169,18 @@ impl Game {
                     self.render(&mut session.screen());
                 },
 
+                State::Paused => {
+                    let mut session = terminal.lock_now().await?;
+                    // Waits for the focus to come back. Resizing still
+                    // requires a new rendering.
+                    if let Some(event) = session.event() {
+                        self.paused_event(event);
+                        if self.state == State::Paused {
+                            self.render(&mut session.screen());
+                        }
+                    }
+                },
+
                 State::BadScreen => {
                     let mut session = terminal.lock_now().await?;
                     // If there is an event, it potentially resized the screen
@@ -188,8 +213,10 @@ impl Game {
             self.full_event(curr_event);
         }
 
-        // Moves the snake.
-        self.move_snake();
+        // Moves the snake, unless the event stopped the game.
+        if self.state == State::Running {
+            self.move_snake();
+        }
     }
 
     /// Handles an event, reacting to the full list of used events, be it
@@ -249,8 +276,37 @@ impl Game {
                 self.resize(resize);
             },
 
-            // Other keys, ignores.
-            Event::Key(_) => (),
+            // Terminal lost focus. Pauses the game.
+            Event::Focus { gained: false } => {
+                self.state = State::Paused;
+            },
+
+            // Other events, ignores.
+            _ => (),
+        }
+    }
+
+    /// Handles an event while the game is paused: resizing, ESC, or focus
+    /// being regained.
+    fn paused_event(&mut self, event: Event) {
+        match event {
+            // ESC was pressed. Cancels the game.
+            Event::Key(KeyEvent { main_key: Key::Esc, .. }) => {
+                self.state = State::Ended(EndKind::Cancel);
+            },
+
+            // Terminal gained focus back. Resumes the game.
+            Event::Focus { gained: true } => {
+                self.state = State::Running;
+            },
+
+            // Screen was resized. Propagates the new size.
+            Event::Resize(resize) => {
+                self.resize(resize);
+            },
+
+            // Other events, ignores.
+            _ => (),
         }
     }
 
@@ -271,8 +327,8 @@ impl Game {
                 }
             },
 
-            // Other keys, ignores.
-            Event::Key(_) => (),
+            // Other events, ignores.
+            _ => (),
         }
     }
 
@@ -376,7 +432,12 @@ impl Game {
         // Centralized, at the top.
         let style = Style::with_colors(colors).align(1, 2);
         // Writes the text into the buffer.
-        screen.styled_text(&self.message, style);
+        let message = if self.state == State::Paused {
+            &self.paused_message
+        } else {
+            &self.message
+        };
+        screen.styled_text(message, style);
     }
 
     /// Resizes the game state about screen size.
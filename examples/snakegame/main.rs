@@ -9,15 +9,13 @@ use crate::game::{EndKind, Game};
 use andiskaz::{
     color::{BasicColor, Color2},
     coord::Vec2,
-    emergency_restore,
     error::Error as AndiskazError,
     style::Style,
     terminal,
     terminal::Terminal,
     tstring,
 };
-use backtrace::Backtrace;
-use std::{panic, process::exit, time::Duration};
+use std::{process::exit, time::Duration};
 
 /// Time interval between game ticks.
 const TICK: Duration = Duration::from_millis(70);
@@ -27,14 +25,6 @@ const WAIT_USER_DELAY: Duration = Duration::from_millis(100);
 /// Asynchronous main of a tokio project.
 #[tokio::main]
 async fn main() {
-    // Sets panic hook so we can see the panic even if terminal was being used
-    // in raw mode.
-    panic::set_hook(Box::new(|info| {
-        let backtrace = Backtrace::new();
-        let _ = emergency_restore();
-        eprintln!("{}\n{:?}", info, backtrace);
-    }));
-
     // Creates and runs a terminal with given settings.
     let result = terminal::Builder::default()
         // Interval between event polling.
@@ -43,6 +33,13 @@ async fn main() {
         .min_screen(Vec2 { x: 50, y: 20 })
         // Interval between rendering frames.
         .frame_time(TICK / 2)
+        // Reports focus changes, so the game pauses when focus is lost.
+        .focus_reporting(true)
+        // Renders less often while not focused.
+        .unfocused_frame_time(TICK * 4)
+        // Restores the terminal and prints the panic with a backtrace on
+        // panic, even if terminal was being used in raw mode.
+        .panic_hook(true)
         // Runs.
         .run(term_main)
         .await;
@@ -0,0 +1,323 @@
+//! This module defines terminal backends: where the terminal's size, raw mode
+//! and input events come from, and where the rendered output goes to. By
+//! default, the [`CrosstermBackend`] is used, which drives the process' own
+//! terminal.
+//!
+//! A custom backend can be given to
+//! [`Builder::backend`](crate::terminal::Builder::backend), e.g. to run an
+//! application over a socket, a pseudo-terminal, or headless in tests.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    color::ColorSupport,
+    coord,
+    coord::Vec2,
+    event::{
+        Event,
+        Key,
+        KeyEvent,
+        MediaKey,
+        ModifierKey,
+        MouseButton,
+        MouseEvent,
+        MouseKind,
+        ResizeEvent,
+    },
+    string::TermString,
+};
+use crossterm::event::{
+    Event as CrosstermEvent,
+    KeyCode as CrosstermKey,
+    KeyEventKind,
+    KeyModifiers as Mod,
+    MediaKeyCode as CrosstermMedia,
+    ModifierKeyCode as CrosstermModifier,
+    MouseButton as CrosstermButton,
+    MouseEventKind as CrosstermMouseKind,
+};
+use std::{
+    fmt,
+    io::{self, Write},
+    time::Duration,
+};
+use tokio::io::AsyncWrite;
+
+/// Output sink of a backend, where rendered frames and terminal commands are
+/// written to. Output is encoded as ANSI escape sequences.
+pub type Output = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// A terminal backend. Provides the terminal size, raw mode, input events and
+/// an output sink.
+///
+/// Methods other than [`Backend::output`] may block for a short time; they are
+/// called inside [`tokio::task::block_in_place`], and therefore require a
+/// multi-threaded runtime, just like the default backend.
+pub trait Backend: fmt::Debug + Send + Sync + 'static {
+    /// Whether this backend can only be used by one terminal application at a
+    /// time, e.g. because it drives the process' own terminal. Running a
+    /// terminal with an exclusive backend while another one is running fails
+    /// with [`AlreadyRunning`](crate::error::AlreadyRunning). Defaults to
+    /// `true`.
+    fn exclusive(&self) -> bool {
+        true
+    }
+
+    /// Enables raw mode, i.e. input is not echoed nor buffered by lines.
+    fn enable_raw_mode(&self) -> io::Result<()>;
+
+    /// Disables raw mode, restoring the usual input handling.
+    fn disable_raw_mode(&self) -> io::Result<()>;
+
+    /// Queries the current size of the terminal.
+    fn size(&self) -> io::Result<Vec2>;
+
+    /// Polls for an input event without waiting for it. Returns `Ok(None)` if
+    /// no event is available yet. Resizes must be reported as
+    /// [`Event::Resize`] carrying the new size; checking it against the
+    /// minimum size is up to the terminal.
+    fn poll_event(&self) -> io::Result<Option<Event>>;
+
+    /// Creates the output sink. Called once for every run of a terminal.
+    fn output(&self) -> Output;
+
+    /// The colors supported by the terminal, used unless overridden through
+    /// [`Builder::color_support`](crate::terminal::Builder::color_support).
+    /// Defaults to RGB colors.
+    fn color_support(&self) -> ColorSupport {
+        ColorSupport::Rgb
+    }
+
+    /// Stops the process until it is continued, as the shell's job control
+    /// does on Ctrl-Z (`SIGTSTP`) and `fg` (`SIGCONT`). Called with the
+    /// terminal already restored; it is set up again once this returns.
+    /// Defaults to not stopping anything, e.g. for backends that do not drive
+    /// the process' own terminal.
+    fn suspend(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes the given bytes to the output synchronously. Used only as a last
+    /// resort to restore the terminal when it is dropped without being cleaned
+    /// up.
+    fn write_blocking(&self, buf: &[u8]) -> io::Result<()>;
+}
+
+/// The default backend: the process' own terminal, through crossterm and the
+/// standard input and output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CrosstermBackend;
+
+impl CrosstermBackend {
+    /// Creates the crossterm backend.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Backend for CrosstermBackend {
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        crossterm::terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        crossterm::terminal::disable_raw_mode()
+    }
+
+    fn size(&self) -> io::Result<Vec2> {
+        let (width, height) = crossterm::terminal::size()?;
+        Ok(Vec2 {
+            y: coord::from_crossterm(height),
+            x: coord::from_crossterm(width),
+        })
+    }
+
+    fn poll_event(&self) -> io::Result<Option<Event>> {
+        while crossterm::event::poll(Duration::from_millis(0))? {
+            if let Some(event) = event_from_crossterm(crossterm::event::read()?)
+            {
+                return Ok(Some(event));
+            }
+        }
+        Ok(None)
+    }
+
+    fn output(&self) -> Output {
+        Box::new(tokio::io::stdout())
+    }
+
+    fn color_support(&self) -> ColorSupport {
+        ColorSupport::detect()
+    }
+
+    #[cfg(unix)]
+    fn suspend(&self) -> io::Result<()> {
+        // Returns only once the process is continued.
+        if unsafe { libc::raise(libc::SIGTSTP) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn suspend(&self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "suspending the process is only supported on unix",
+        ))
+    }
+
+    fn write_blocking(&self, buf: &[u8]) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(buf)?;
+        stdout.flush()
+    }
+}
+
+/// Translates a crossterm event to an Andiskaz event, if it is supported.
+fn event_from_crossterm(crossterm: CrosstermEvent) -> Option<Event> {
+    match crossterm {
+        CrosstermEvent::Key(key) if key.kind != KeyEventKind::Release => {
+            Some(Event::Key(KeyEvent {
+                main_key: key_from_crossterm(key.code),
+                ctrl: key.modifiers.intersects(Mod::CONTROL),
+                alt: key.modifiers.intersects(Mod::ALT),
+                shift: key.modifiers.intersects(Mod::SHIFT),
+            }))
+        },
+
+        CrosstermEvent::Key(_) => None,
+
+        CrosstermEvent::Mouse(mouse) => Some(Event::Mouse(MouseEvent {
+            kind: mouse_kind_from_crossterm(mouse.kind),
+            pos: Vec2 {
+                x: coord::from_crossterm(mouse.column),
+                y: coord::from_crossterm(mouse.row),
+            },
+            ctrl: mouse.modifiers.intersects(Mod::CONTROL),
+            alt: mouse.modifiers.intersects(Mod::ALT),
+            shift: mouse.modifiers.intersects(Mod::SHIFT),
+        })),
+
+        CrosstermEvent::FocusGained => Some(Event::Focus { gained: true }),
+
+        CrosstermEvent::FocusLost => Some(Event::Focus { gained: false }),
+
+        CrosstermEvent::Paste(text) => {
+            Some(Event::Paste(TermString::new_lossy(text)))
+        },
+
+        CrosstermEvent::Resize(width, height) => {
+            let size = Vec2 {
+                x: coord::from_crossterm(width),
+                y: coord::from_crossterm(height),
+            };
+            Some(Event::Resize(ResizeEvent { size: Some(size) }))
+        },
+    }
+}
+
+/// Translates a crossterm key to an Andiskaz key.
+fn key_from_crossterm(crossterm: CrosstermKey) -> Key {
+    match crossterm {
+        CrosstermKey::Esc => Key::Esc,
+        CrosstermKey::Backspace => Key::Backspace,
+        CrosstermKey::Enter => Key::Enter,
+        CrosstermKey::Up => Key::Up,
+        CrosstermKey::Down => Key::Down,
+        CrosstermKey::Left => Key::Left,
+        CrosstermKey::Right => Key::Right,
+        CrosstermKey::Char(ch) => Key::Char(ch),
+        CrosstermKey::Tab => Key::Tab,
+        CrosstermKey::BackTab => Key::BackTab,
+        CrosstermKey::Delete => Key::Delete,
+        CrosstermKey::Insert => Key::Insert,
+        CrosstermKey::Home => Key::Home,
+        CrosstermKey::End => Key::End,
+        CrosstermKey::PageUp => Key::PageUp,
+        CrosstermKey::PageDown => Key::PageDown,
+        CrosstermKey::F(number) => Key::F(number),
+        CrosstermKey::Null => Key::Null,
+        CrosstermKey::CapsLock => Key::CapsLock,
+        CrosstermKey::ScrollLock => Key::ScrollLock,
+        CrosstermKey::NumLock => Key::NumLock,
+        CrosstermKey::PrintScreen => Key::PrintScreen,
+        CrosstermKey::Pause => Key::Pause,
+        CrosstermKey::Menu => Key::Menu,
+        CrosstermKey::KeypadBegin => Key::KeypadBegin,
+        CrosstermKey::Media(media) => Key::Media(media_from_crossterm(media)),
+        CrosstermKey::Modifier(modifier) => {
+            Key::Modifier(modifier_from_crossterm(modifier))
+        },
+    }
+}
+
+/// Translates a crossterm media key to an Andiskaz media key.
+fn media_from_crossterm(crossterm: CrosstermMedia) -> MediaKey {
+    match crossterm {
+        CrosstermMedia::Play => MediaKey::Play,
+        CrosstermMedia::Pause => MediaKey::Pause,
+        CrosstermMedia::PlayPause => MediaKey::PlayPause,
+        CrosstermMedia::Reverse => MediaKey::Reverse,
+        CrosstermMedia::Stop => MediaKey::Stop,
+        CrosstermMedia::FastForward => MediaKey::FastForward,
+        CrosstermMedia::Rewind => MediaKey::Rewind,
+        CrosstermMedia::TrackNext => MediaKey::TrackNext,
+        CrosstermMedia::TrackPrevious => MediaKey::TrackPrevious,
+        CrosstermMedia::Record => MediaKey::Record,
+        CrosstermMedia::LowerVolume => MediaKey::LowerVolume,
+        CrosstermMedia::RaiseVolume => MediaKey::RaiseVolume,
+        CrosstermMedia::MuteVolume => MediaKey::MuteVolume,
+    }
+}
+
+/// Translates a crossterm modifier key to an Andiskaz modifier key.
+fn modifier_from_crossterm(crossterm: CrosstermModifier) -> ModifierKey {
+    match crossterm {
+        CrosstermModifier::LeftShift => ModifierKey::LeftShift,
+        CrosstermModifier::LeftControl => ModifierKey::LeftControl,
+        CrosstermModifier::LeftAlt => ModifierKey::LeftAlt,
+        CrosstermModifier::LeftSuper => ModifierKey::LeftSuper,
+        CrosstermModifier::LeftHyper => ModifierKey::LeftHyper,
+        CrosstermModifier::LeftMeta => ModifierKey::LeftMeta,
+        CrosstermModifier::RightShift => ModifierKey::RightShift,
+        CrosstermModifier::RightControl => ModifierKey::RightControl,
+        CrosstermModifier::RightAlt => ModifierKey::RightAlt,
+        CrosstermModifier::RightSuper => ModifierKey::RightSuper,
+        CrosstermModifier::RightHyper => ModifierKey::RightHyper,
+        CrosstermModifier::RightMeta => ModifierKey::RightMeta,
+        CrosstermModifier::IsoLevel3Shift => ModifierKey::IsoLevel3Shift,
+        CrosstermModifier::IsoLevel5Shift => ModifierKey::IsoLevel5Shift,
+    }
+}
+
+/// Translates a crossterm mouse button to an Andiskaz mouse button.
+fn mouse_button_from_crossterm(crossterm: CrosstermButton) -> MouseButton {
+    match crossterm {
+        CrosstermButton::Left => MouseButton::Left,
+        CrosstermButton::Right => MouseButton::Right,
+        CrosstermButton::Middle => MouseButton::Middle,
+    }
+}
+
+/// Translates a crossterm mouse event kind to an Andiskaz mouse event kind.
+fn mouse_kind_from_crossterm(crossterm: CrosstermMouseKind) -> MouseKind {
+    match crossterm {
+        CrosstermMouseKind::Down(button) => {
+            MouseKind::Press(mouse_button_from_crossterm(button))
+        },
+        CrosstermMouseKind::Up(button) => {
+            MouseKind::Release(mouse_button_from_crossterm(button))
+        },
+        CrosstermMouseKind::Drag(button) => {
+            MouseKind::Drag(mouse_button_from_crossterm(button))
+        },
+        CrosstermMouseKind::Moved => MouseKind::Move,
+        CrosstermMouseKind::ScrollUp => MouseKind::ScrollUp,
+        CrosstermMouseKind::ScrollDown => MouseKind::ScrollDown,
+        CrosstermMouseKind::ScrollLeft => MouseKind::ScrollLeft,
+        CrosstermMouseKind::ScrollRight => MouseKind::ScrollRight,
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    backend::{event_from_crossterm, key_from_crossterm},
+    coord::Vec2,
+    event::{
+        Event,
+        Key,
+        KeyEvent,
+        MediaKey,
+        ModifierKey,
+        MouseButton,
+        MouseEvent,
+        MouseKind,
+    },
+};
+use crossterm::event::{
+    Event as CrosstermEvent,
+    KeyCode as CrosstermKey,
+    KeyEvent as CrosstermKeyEvent,
+    KeyEventKind,
+    KeyModifiers as Mod,
+    MediaKeyCode as CrosstermMedia,
+    ModifierKeyCode as CrosstermModifier,
+    MouseButton as CrosstermButton,
+    MouseEvent as CrosstermMouse,
+    MouseEventKind as CrosstermMouseKind,
+};
+
+#[test]
+fn keys() {
+    let table = [
+        (CrosstermKey::Esc, Key::Esc),
+        (CrosstermKey::Backspace, Key::Backspace),
+        (CrosstermKey::Enter, Key::Enter),
+        (CrosstermKey::Up, Key::Up),
+        (CrosstermKey::Down, Key::Down),
+        (CrosstermKey::Left, Key::Left),
+        (CrosstermKey::Right, Key::Right),
+        (CrosstermKey::Char('ç'), Key::Char('ç')),
+        (CrosstermKey::Tab, Key::Tab),
+        (CrosstermKey::BackTab, Key::BackTab),
+        (CrosstermKey::Delete, Key::Delete),
+        (CrosstermKey::Insert, Key::Insert),
+        (CrosstermKey::Home, Key::Home),
+        (CrosstermKey::End, Key::End),
+        (CrosstermKey::PageUp, Key::PageUp),
+        (CrosstermKey::PageDown, Key::PageDown),
+        (CrosstermKey::F(1), Key::F(1)),
+        (CrosstermKey::F(24), Key::F(24)),
+        (CrosstermKey::Null, Key::Null),
+        (CrosstermKey::CapsLock, Key::CapsLock),
+        (CrosstermKey::ScrollLock, Key::ScrollLock),
+        (CrosstermKey::NumLock, Key::NumLock),
+        (CrosstermKey::PrintScreen, Key::PrintScreen),
+        (CrosstermKey::Pause, Key::Pause),
+        (CrosstermKey::Menu, Key::Menu),
+        (CrosstermKey::KeypadBegin, Key::KeypadBegin),
+    ];
+    for &(crossterm, key) in &table {
+        assert_eq!(key_from_crossterm(crossterm), key);
+    }
+}
+
+#[test]
+fn media_keys() {
+    let table = [
+        (CrosstermMedia::Play, MediaKey::Play),
+        (CrosstermMedia::Pause, MediaKey::Pause),
+        (CrosstermMedia::PlayPause, MediaKey::PlayPause),
+        (CrosstermMedia::Reverse, MediaKey::Reverse),
+        (CrosstermMedia::Stop, MediaKey::Stop),
+        (CrosstermMedia::FastForward, MediaKey::FastForward),
+        (CrosstermMedia::Rewind, MediaKey::Rewind),
+        (CrosstermMedia::TrackNext, MediaKey::TrackNext),
+        (CrosstermMedia::TrackPrevious, MediaKey::TrackPrevious),
+        (CrosstermMedia::Record, MediaKey::Record),
+        (CrosstermMedia::LowerVolume, MediaKey::LowerVolume),
+        (CrosstermMedia::RaiseVolume, MediaKey::RaiseVolume),
+        (CrosstermMedia::MuteVolume, MediaKey::MuteVolume),
+    ];
+    for &(crossterm, media) in &table {
+        let key = key_from_crossterm(CrosstermKey::Media(crossterm));
+        assert_eq!(key, Key::Media(media));
+    }
+}
+
+#[test]
+fn modifier_keys() {
+    let table = [
+        (CrosstermModifier::LeftShift, ModifierKey::LeftShift),
+        (CrosstermModifier::LeftControl, ModifierKey::LeftControl),
+        (CrosstermModifier::LeftAlt, ModifierKey::LeftAlt),
+        (CrosstermModifier::LeftSuper, ModifierKey::LeftSuper),
+        (CrosstermModifier::LeftHyper, ModifierKey::LeftHyper),
+        (CrosstermModifier::LeftMeta, ModifierKey::LeftMeta),
+        (CrosstermModifier::RightShift, ModifierKey::RightShift),
+        (CrosstermModifier::RightControl, ModifierKey::RightControl),
+        (CrosstermModifier::RightAlt, ModifierKey::RightAlt),
+        (CrosstermModifier::RightSuper, ModifierKey::RightSuper),
+        (CrosstermModifier::RightHyper, ModifierKey::RightHyper),
+        (CrosstermModifier::RightMeta, ModifierKey::RightMeta),
+        (CrosstermModifier::IsoLevel3Shift, ModifierKey::IsoLevel3Shift),
+        (CrosstermModifier::IsoLevel5Shift, ModifierKey::IsoLevel5Shift),
+    ];
+    for &(crossterm, modifier) in &table {
+        let key = key_from_crossterm(CrosstermKey::Modifier(crossterm));
+        assert_eq!(key, Key::Modifier(modifier));
+    }
+}
+
+#[test]
+fn key_events() {
+    let table = [
+        (KeyEventKind::Press, Mod::NONE, Some((false, false, false))),
+        (KeyEventKind::Repeat, Mod::CONTROL, Some((true, false, false))),
+        (KeyEventKind::Press, Mod::ALT, Some((false, true, false))),
+        (KeyEventKind::Press, Mod::SHIFT, Some((false, false, true))),
+        (KeyEventKind::Release, Mod::NONE, None),
+        (KeyEventKind::Release, Mod::CONTROL, None),
+    ];
+    for &(kind, modifiers, expected) in &table {
+        let crossterm = CrosstermKeyEvent::new_with_kind(
+            CrosstermKey::Home,
+            modifiers,
+            kind,
+        );
+        let expected = expected.map(|(ctrl, alt, shift)| {
+            Event::Key(KeyEvent { main_key: Key::Home, ctrl, alt, shift })
+        });
+        assert_eq!(
+            event_from_crossterm(CrosstermEvent::Key(crossterm)),
+            expected
+        );
+    }
+}
+
+#[test]
+fn mouse_events() {
+    let table = [
+        (
+            CrosstermMouseKind::Down(CrosstermButton::Left),
+            MouseKind::Press(MouseButton::Left),
+        ),
+        (
+            CrosstermMouseKind::Up(CrosstermButton::Right),
+            MouseKind::Release(MouseButton::Right),
+        ),
+        (
+            CrosstermMouseKind::Drag(CrosstermButton::Middle),
+            MouseKind::Drag(MouseButton::Middle),
+        ),
+        (CrosstermMouseKind::Moved, MouseKind::Move),
+        (CrosstermMouseKind::ScrollUp, MouseKind::ScrollUp),
+        (CrosstermMouseKind::ScrollDown, MouseKind::ScrollDown),
+        (CrosstermMouseKind::ScrollLeft, MouseKind::ScrollLeft),
+        (CrosstermMouseKind::ScrollRight, MouseKind::ScrollRight),
+    ];
+    for &(crossterm, kind) in &table {
+        let mouse = CrosstermMouse {
+            kind: crossterm,
+            column: 7,
+            row: 3,
+            modifiers: Mod::NONE,
+        };
+        let expected = MouseEvent {
+            kind,
+            pos: Vec2 { x: 7, y: 3 },
+            ctrl: false,
+            alt: false,
+            shift: false,
+        };
+        assert_eq!(
+            event_from_crossterm(CrosstermEvent::Mouse(mouse)),
+            Some(Event::Mouse(expected))
+        );
+    }
+}
+
+#[test]
+fn mouse_modifiers() {
+    let table = [
+        (Mod::CONTROL, (true, false, false)),
+        (Mod::ALT, (false, true, false)),
+        (Mod::SHIFT, (false, false, true)),
+        (Mod::CONTROL | Mod::ALT | Mod::SHIFT, (true, true, true)),
+    ];
+    for &(modifiers, (ctrl, alt, shift)) in &table {
+        let mouse = CrosstermMouse {
+            kind: CrosstermMouseKind::Moved,
+            column: 0,
+            row: 0,
+            modifiers,
+        };
+        let expected = MouseEvent {
+            kind: MouseKind::Move,
+            pos: Vec2 { x: 0, y: 0 },
+            ctrl,
+            alt,
+            shift,
+        };
+        assert_eq!(
+            event_from_crossterm(CrosstermEvent::Mouse(mouse)),
+            Some(Event::Mouse(expected))
+        );
+    }
+}
+
+#[test]
+fn paste_is_one_event() {
+    let text = "first\r\nsecond\tthird\x1b[31m\x07";
+    let event = event_from_crossterm(CrosstermEvent::Paste(text.to_owned()));
+    assert_eq!(
+        event,
+        Some(Event::Paste(tstring!["first��second�third�[31m�"]))
+    );
+}
@@ -10,6 +10,7 @@ mod basic;
 mod eight_bit;
 mod rgb;
 mod pair;
+mod support;
 
 pub use self::{
     basic::BasicColor,
@@ -27,6 +28,7 @@ pub use self::{
         Updater,
     },
     rgb::RgbColor,
+    support::ColorSupport,
 };
 use crossterm::style::Color as CrosstermColor;
 use std::ops::Not;
@@ -54,6 +56,38 @@ impl Color {
             Color::Rgb(color) => color.to_crossterm(),
         }
     }
+
+    /// Approximates this color in RGB, using xterm's default palette for
+    /// basic and 8-bit colors.
+    pub fn to_rgb(self) -> RgbColor {
+        match self {
+            Color::Basic(color) => color.to_rgb(),
+            Color::EightBit(color) => color.to_rgb(),
+            Color::Rgb(color) => color,
+        }
+    }
+
+    /// Approximates this color by the nearest one shown by a terminal with the
+    /// given support, e.g. RGB colors are approximated by 8-bit colors, and
+    /// 8-bit colors by basic colors. Colors already supported are kept as
+    /// they are. With [`ColorSupport::NoColor`], colors are approximated by
+    /// basic colors, although they are not shown at all.
+    pub fn downsample(self, support: ColorSupport) -> Self {
+        match (self, support) {
+            (Color::Rgb(color), ColorSupport::EightBit) => {
+                Color::EightBit(Color8Bit::nearest(color))
+            },
+            (
+                Color::Rgb(color),
+                ColorSupport::Basic | ColorSupport::NoColor,
+            ) => Color::Basic(BasicColor::nearest(color)),
+            (
+                Color::EightBit(color),
+                ColorSupport::Basic | ColorSupport::NoColor,
+            ) => Color::Basic(color.to_basic()),
+            _ => self,
+        }
+    }
 }
 
 impl ApproxBrightness for Color {
@@ -1,4 +1,4 @@
-use crate::color::{ApproxBrightness, BadBasicColor, Brightness};
+use crate::color::{ApproxBrightness, BadBasicColor, Brightness, RgbColor};
 use crossterm::style::Color as CrosstermColor;
 use std::{convert::TryFrom, ops::Not};
 
@@ -62,6 +62,38 @@ impl BasicColor {
             BasicColor::White => CrosstermColor::White,
         }
     }
+
+    /// Approximates this color in RGB, using xterm's default palette.
+    pub fn to_rgb(self) -> RgbColor {
+        let (red, green, blue) = match self {
+            BasicColor::Black => (0, 0, 0),
+            BasicColor::DarkRed => (205, 0, 0),
+            BasicColor::DarkGreen => (0, 205, 0),
+            BasicColor::DarkYellow => (205, 205, 0),
+            BasicColor::DarkBlue => (0, 0, 238),
+            BasicColor::DarkMagenta => (205, 0, 205),
+            BasicColor::DarkCyan => (0, 205, 205),
+            BasicColor::LightGray => (229, 229, 229),
+            BasicColor::DarkGray => (127, 127, 127),
+            BasicColor::LightRed => (255, 0, 0),
+            BasicColor::LightGreen => (0, 255, 0),
+            BasicColor::LightYellow => (255, 255, 0),
+            BasicColor::LightBlue => (92, 92, 255),
+            BasicColor::LightMagenta => (255, 0, 255),
+            BasicColor::LightCyan => (0, 255, 255),
+            BasicColor::White => (255, 255, 255),
+        };
+        RgbColor { red, green, blue }
+    }
+
+    /// The basic color nearest to the given RGB color, using xterm's default
+    /// palette.
+    pub fn nearest(color: RgbColor) -> Self {
+        (0 .. 16)
+            .map(|code| Self::try_from(code).unwrap())
+            .min_by_key(|basic| basic.to_rgb().distance(color))
+            .unwrap()
+    }
 }
 
 impl TryFrom<u8> for BasicColor {
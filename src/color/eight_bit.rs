@@ -7,6 +7,7 @@ use crate::color::{
     BadGrayColor,
     BasicColor,
     Brightness,
+    RgbColor,
 };
 use crossterm::style::Color as CrosstermColor;
 use std::{convert::TryFrom, fmt, ops::Not};
@@ -67,6 +68,28 @@ impl CmyColor {
         self.code
     }
 
+    /// Approximates this color in RGB, using xterm's default palette. Cyan,
+    /// magenta and yellow levels map to red, green and blue intensities.
+    pub fn to_rgb(self) -> RgbColor {
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        RgbColor {
+            red: LEVELS[usize::from(self.cyan())],
+            green: LEVELS[usize::from(self.magenta())],
+            blue: LEVELS[usize::from(self.yellow())],
+        }
+    }
+
+    /// The CMY color nearest to the given RGB color, using xterm's default
+    /// palette.
+    pub fn nearest(color: RgbColor) -> Self {
+        let level = |value: u8| match value {
+            0 ..= 47 => 0,
+            48 ..= 114 => 1,
+            _ => (value - 35) / 40,
+        };
+        Self::new(level(color.red), level(color.green), level(color.blue))
+    }
+
     /// Sets the cyan component.
     ///
     /// # Panics
@@ -180,6 +203,22 @@ impl GrayColor {
     pub const fn brightness(self) -> u8 {
         self.brightness
     }
+
+    /// The gray-scale color nearest to the given RGB color, using xterm's
+    /// default palette.
+    pub fn nearest(color: RgbColor) -> Self {
+        let sum = u16::from(color.red)
+            + u16::from(color.green)
+            + u16::from(color.blue);
+        let brightness = (sum / 3).saturating_sub(3) / 10;
+        Self::new(brightness.min(u16::from(Self::MAX.brightness)) as u8)
+    }
+
+    /// Approximates this color in RGB, using xterm's default palette.
+    pub fn to_rgb(self) -> RgbColor {
+        let level = 8 + self.brightness * 10;
+        RgbColor { red: level, green: level, blue: level }
+    }
 }
 
 impl Not for GrayColor {
@@ -318,6 +357,36 @@ impl Color8Bit {
     pub(crate) fn to_crossterm(self) -> CrosstermColor {
         CrosstermColor::AnsiValue(self.code())
     }
+
+    /// The CMY or gray-scale color nearest to the given RGB color, using
+    /// xterm's default palette. Basic colors are never chosen, since
+    /// terminals often customize them.
+    pub fn nearest(color: RgbColor) -> Self {
+        let cmy = CmyColor::nearest(color);
+        let gray = GrayColor::nearest(color);
+        if gray.to_rgb().distance(color) < cmy.to_rgb().distance(color) {
+            Self::from(gray)
+        } else {
+            Self::from(cmy)
+        }
+    }
+
+    /// The basic color nearest to this one. Basic colors are kept as they are.
+    pub fn to_basic(self) -> BasicColor {
+        match self.kind() {
+            Color8BitKind::Basic(color) => color,
+            _ => BasicColor::nearest(self.to_rgb()),
+        }
+    }
+
+    /// Approximates this color in RGB, using xterm's default palette.
+    pub fn to_rgb(self) -> RgbColor {
+        match self.kind() {
+            Color8BitKind::Basic(color) => color.to_rgb(),
+            Color8BitKind::Cmy(color) => color.to_rgb(),
+            Color8BitKind::Gray(color) => color.to_rgb(),
+        }
+    }
 }
 
 impl fmt::Debug for Color8Bit {
@@ -30,6 +30,18 @@ impl RgbColor {
         CrosstermColor::Rgb { r: self.red, g: self.green, b: self.blue }
     }
 
+    /// Squared euclidean distance between this color and the other one, used
+    /// to find the nearest color in a palette.
+    pub(crate) fn distance(self, other: Self) -> u32 {
+        let channel = |this: u8, other: u8| {
+            let diff = i32::from(this) - i32::from(other);
+            (diff * diff) as u32
+        };
+        channel(self.red, other.red)
+            + channel(self.green, other.green)
+            + channel(self.blue, other.blue)
+    }
+
     /// Creates an RGB color from the given channels.
     fn from_channels(channels: [Channel; 3]) -> Self {
         Self {
@@ -0,0 +1,94 @@
+//! This module provides detection of the colors supported by the terminal.
+
+use crate::{
+    color::{ApproxBrightness, Color, Color2},
+    tile::Attributes,
+};
+use crossterm::style::Color as CrosstermColor;
+use std::env;
+
+/// The colors a terminal is able to show. Colors that are not supported are
+/// approximated by the nearest supported color when rendered (see
+/// [`Color::downsample`]). Variants are ordered from the least to the most
+/// capable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ColorSupport {
+    /// No colors at all: the terminal's default colors are used, e.g. because
+    /// the user set `NO_COLOR`. Tiles with a background lighter than their
+    /// foreground, such as selected items, are shown in reverse video.
+    NoColor,
+    /// Only the 16 basic colors.
+    Basic,
+    /// ANSI 8-bit colors (256 colors).
+    EightBit,
+    /// RGB colors, also known as truecolor or 24-bit colors.
+    #[default]
+    Rgb,
+}
+
+impl ColorSupport {
+    /// Detects the colors supported by the process' terminal from the
+    /// environment: `NO_COLOR`, `COLORTERM` and `TERM`. Colors are only
+    /// dropped if `NO_COLOR` is set, or if `TERM` is empty or `dumb`.
+    pub fn detect() -> Self {
+        let var = |name| env::var(name).ok();
+        Self::from_env(
+            var("NO_COLOR").as_deref(),
+            var("COLORTERM").as_deref(),
+            var("TERM").as_deref(),
+        )
+    }
+
+    /// Detects the supported colors given the values of `NO_COLOR`,
+    /// `COLORTERM` and `TERM`, if set.
+    pub(crate) fn from_env(
+        no_color: Option<&str>,
+        colorterm: Option<&str>,
+        term: Option<&str>,
+    ) -> Self {
+        if no_color.is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::NoColor;
+        }
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorSupport::Rgb;
+        }
+        match term {
+            // Nothing is known, e.g. the Windows console does not set TERM,
+            // and it has supported RGB colors since Windows 10.
+            None => ColorSupport::Rgb,
+            Some("") | Some("dumb") => ColorSupport::NoColor,
+            Some(term)
+                if term.ends_with("-direct")
+                    || term.contains("truecolor")
+                    || term.contains("24bit") =>
+            {
+                ColorSupport::Rgb
+            },
+            Some(term) if term.contains("256color") => ColorSupport::EightBit,
+            Some(_) => ColorSupport::Basic,
+        }
+    }
+
+    /// Translates the given color to a crossterm color shown by a terminal
+    /// with this support.
+    pub(crate) fn to_crossterm(self, color: Color) -> CrosstermColor {
+        match self {
+            ColorSupport::NoColor => CrosstermColor::Reset,
+            _ => color.downsample(self).to_crossterm(),
+        }
+    }
+
+    /// Attributes shown by a terminal with this support in addition to those
+    /// of a tile with the given colors. Without colors, a background lighter
+    /// than the foreground (e.g. of a selected item, drawn with inverted
+    /// colors) is shown in reverse video, so that it still stands out.
+    pub(crate) fn extra_attributes(self, colors: Color2) -> Attributes {
+        let inverted = colors.background.approx_brightness()
+            > colors.foreground.approx_brightness();
+        if self == ColorSupport::NoColor && inverted {
+            Attributes::REVERSE
+        } else {
+            Attributes::NONE
+        }
+    }
+}
@@ -1,14 +1,21 @@
-use crate::color::{
-    ApproxBrightness,
-    Brightness,
-    CmyColor,
-    Color2,
-    ContrastFgWithBg,
-    GrayColor,
-    RgbColor,
-    UpdateBg,
-    UpdateFg,
-    Updater,
+use crate::{
+    color::{
+        ApproxBrightness,
+        BasicColor,
+        Brightness,
+        CmyColor,
+        Color,
+        Color2,
+        Color8Bit,
+        ColorSupport,
+        ContrastFgWithBg,
+        GrayColor,
+        RgbColor,
+        UpdateBg,
+        UpdateFg,
+        Updater,
+    },
+    tile::Attributes,
 };
 
 #[test]
@@ -85,3 +92,100 @@ fn updaters() {
         }
     );
 }
+
+#[test]
+fn xterm_palette() {
+    let rgb = |red, green, blue| RgbColor { red, green, blue };
+    assert_eq!(BasicColor::DarkBlue.to_rgb(), rgb(0, 0, 238));
+    assert_eq!(CmyColor::new(5, 0, 2).to_rgb(), rgb(255, 0, 135));
+    assert_eq!(GrayColor::new(23).to_rgb(), rgb(238, 238, 238));
+    assert_eq!(Color8Bit::from(CmyColor::new(1, 1, 1)).code(), 59);
+    assert_eq!(
+        Color::from(Color8Bit::from(CmyColor::new(1, 1, 1))).to_rgb(),
+        rgb(95, 95, 95)
+    );
+    assert_eq!(Color::from(rgb(1, 2, 3)).to_rgb(), rgb(1, 2, 3));
+}
+
+#[test]
+fn detect_color_support() {
+    let detect = ColorSupport::from_env;
+    assert_eq!(
+        detect(Some("1"), Some("truecolor"), Some("xterm")),
+        ColorSupport::NoColor
+    );
+    assert_eq!(detect(Some(""), None, Some("xterm")), ColorSupport::Basic);
+    assert_eq!(detect(None, Some("24bit"), Some("xterm")), ColorSupport::Rgb);
+    assert_eq!(detect(None, None, Some("xterm-direct")), ColorSupport::Rgb);
+    assert_eq!(
+        detect(None, None, Some("xterm-256color")),
+        ColorSupport::EightBit
+    );
+    assert_eq!(detect(None, None, Some("linux")), ColorSupport::Basic);
+    assert_eq!(detect(None, None, Some("dumb")), ColorSupport::NoColor);
+    assert_eq!(detect(None, None, Some("")), ColorSupport::NoColor);
+    assert_eq!(detect(None, None, None), ColorSupport::Rgb);
+}
+
+#[test]
+fn inverted_colors_without_color() {
+    let selected = !Color2::default();
+    assert_eq!(
+        ColorSupport::NoColor.extra_attributes(selected),
+        Attributes::REVERSE
+    );
+    assert_eq!(
+        ColorSupport::NoColor.extra_attributes(Color2::default()),
+        Attributes::NONE
+    );
+    assert_eq!(
+        ColorSupport::Basic.extra_attributes(selected),
+        Attributes::NONE
+    );
+}
+
+#[test]
+fn nearest_colors() {
+    let rgb = |red, green, blue| RgbColor { red, green, blue };
+    assert_eq!(CmyColor::nearest(rgb(100, 140, 250)), CmyColor::new(1, 2, 5));
+    assert_eq!(GrayColor::nearest(rgb(0, 0, 0)), GrayColor::new(0));
+    assert_eq!(GrayColor::nearest(rgb(120, 121, 122)), GrayColor::new(11));
+    assert_eq!(GrayColor::nearest(rgb(255, 255, 255)), GrayColor::new(23));
+    assert_eq!(
+        Color8Bit::nearest(rgb(255, 0, 135)),
+        Color8Bit::from(CmyColor::new(5, 0, 2))
+    );
+    assert_eq!(
+        Color8Bit::nearest(rgb(120, 121, 122)),
+        Color8Bit::from(GrayColor::new(11))
+    );
+    assert_eq!(BasicColor::nearest(rgb(10, 190, 20)), BasicColor::DarkGreen);
+    assert_eq!(
+        Color8Bit::from(CmyColor::new(5, 5, 0)).to_basic(),
+        BasicColor::LightYellow
+    );
+    assert_eq!(
+        Color8Bit::from(BasicColor::LightBlue).to_basic(),
+        BasicColor::LightBlue
+    );
+}
+
+#[test]
+fn downsample() {
+    let color = Color::from(RgbColor { red: 250, green: 10, blue: 5 });
+    assert_eq!(color.downsample(ColorSupport::Rgb), color);
+    assert_eq!(
+        color.downsample(ColorSupport::EightBit),
+        Color::from(CmyColor::new(5, 0, 0))
+    );
+    assert_eq!(
+        color.downsample(ColorSupport::Basic),
+        Color::from(BasicColor::LightRed)
+    );
+    let color = Color::from(GrayColor::new(2));
+    assert_eq!(color.downsample(ColorSupport::EightBit), color);
+    assert_eq!(
+        color.downsample(ColorSupport::Basic),
+        Color::from(BasicColor::Black)
+    );
+}
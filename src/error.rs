@@ -34,6 +34,21 @@ impl fmt::Display for ServicesOff {
 
 impl ErrorTrait for ServicesOff {}
 
+/// Error returned when parsing a malformed event log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadEventLog {
+    /// The line (starting from 1) that could not be parsed.
+    pub line: usize,
+}
+
+impl fmt::Display for BadEventLog {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "malformed event log at line {}", self.line)
+    }
+}
+
+impl ErrorTrait for BadEventLog {}
+
 /// Error returned when accessing the clipboard fails.
 #[cfg(feature = "clipboard")]
 #[derive(Debug)]
@@ -98,6 +113,8 @@ pub enum ErrorKind {
     AlreadyRunning(AlreadyRunning),
     /// Event listener and/or renderer disconnected.
     ServicesOff(ServicesOff),
+    /// An event log could not be parsed.
+    BadEventLog(BadEventLog),
     /// This is an IO error.
     IO(io::Error),
     /// This is a formatting error.
@@ -122,6 +139,7 @@ impl ErrorKind {
         match self {
             ErrorKind::AlreadyRunning(error) => error,
             ErrorKind::ServicesOff(error) => error,
+            ErrorKind::BadEventLog(error) => error,
             ErrorKind::IO(error) => error,
             ErrorKind::Fmt(error) => error,
             ErrorKind::ParseInt(error) => error,
@@ -146,6 +164,12 @@ impl From<ServicesOff> for ErrorKind {
     }
 }
 
+impl From<BadEventLog> for ErrorKind {
+    fn from(error: BadEventLog) -> Self {
+        ErrorKind::BadEventLog(error)
+    }
+}
+
 impl From<io::Error> for ErrorKind {
     fn from(error: io::Error) -> Self {
         ErrorKind::IO(error)
@@ -243,6 +267,12 @@ impl From<ServicesOff> for Error {
     }
 }
 
+impl From<BadEventLog> for Error {
+    fn from(error: BadEventLog) -> Self {
+        Self::new(ErrorKind::from(error))
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
         Self::new(ErrorKind::from(error))
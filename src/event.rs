@@ -1,12 +1,24 @@
 //! This module defines input events of a terminal.
 
+#[cfg(test)]
+mod test;
+
 mod reactor;
+mod log;
 
-use crate::coord::Vec2;
-use std::sync::Mutex;
-use tokio::sync::Notify;
+use crate::{coord::Vec2, string::TermString};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    mem,
+    sync::Mutex,
+};
+use tokio::sync::{futures::Notified, Notify};
 
-pub(crate) use reactor::Reactor;
+pub use self::log::{EventLog, LogEntry};
+pub(crate) use self::{
+    log::{EventRecorder, Replay},
+    reactor::Reactor,
+};
 
 /// Epoch integer for our channel's versions. Hopefully, it won't overflow.
 pub type Epoch = u128;
@@ -31,6 +43,117 @@ pub enum Key {
     Enter,
     /// The backspace key
     Backspace,
+    /// The tab key. Preferred over `Char('\t')`.
+    Tab,
+    /// The tab key pressed together with shift.
+    BackTab,
+    /// The delete key.
+    Delete,
+    /// The insert key.
+    Insert,
+    /// The home key.
+    Home,
+    /// The end key.
+    End,
+    /// The page up key.
+    PageUp,
+    /// The page down key.
+    PageDown,
+    /// A function key. E.g. `Key::F(1)` for F1, or `Key::F(12)` for F12.
+    F(u8),
+    /// The null key, sent by some terminals for ctrl + space.
+    Null,
+    /// The caps lock key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    CapsLock,
+    /// The scroll lock key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    ScrollLock,
+    /// The num lock key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    NumLock,
+    /// The print screen key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    PrintScreen,
+    /// The pause key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    Pause,
+    /// The menu key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    Menu,
+    /// The "begin" key, often mapped to 5 in the numeric keypad when num lock
+    /// is off. Only reported by terminals supporting keyboard enhancements.
+    KeypadBegin,
+    /// A media key. Only reported by terminals supporting keyboard
+    /// enhancements.
+    Media(MediaKey),
+    /// A modifier key pressed alone. Only reported by terminals supporting
+    /// keyboard enhancements.
+    Modifier(ModifierKey),
+}
+
+/// A media key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKey {
+    /// Play media.
+    Play,
+    /// Pause media.
+    Pause,
+    /// Toggle between play and pause.
+    PlayPause,
+    /// Play media in reverse.
+    Reverse,
+    /// Stop media.
+    Stop,
+    /// Fast-forward media.
+    FastForward,
+    /// Rewind media.
+    Rewind,
+    /// Go to the next track.
+    TrackNext,
+    /// Go to the previous track.
+    TrackPrevious,
+    /// Record media.
+    Record,
+    /// Lower the volume.
+    LowerVolume,
+    /// Raise the volume.
+    RaiseVolume,
+    /// Mute or unmute the volume.
+    MuteVolume,
+}
+
+/// A modifier key, when reported as a key on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierKey {
+    /// The left shift key.
+    LeftShift,
+    /// The left control key.
+    LeftControl,
+    /// The left alt key.
+    LeftAlt,
+    /// The left super key (e.g. "Windows" or "Command").
+    LeftSuper,
+    /// The left hyper key.
+    LeftHyper,
+    /// The left meta key.
+    LeftMeta,
+    /// The right shift key.
+    RightShift,
+    /// The right control key.
+    RightControl,
+    /// The right alt key.
+    RightAlt,
+    /// The right super key (e.g. "Windows" or "Command").
+    RightSuper,
+    /// The right hyper key.
+    RightHyper,
+    /// The right meta key.
+    RightMeta,
+    /// The ISO level 3 shift key (often "Alt Gr").
+    IsoLevel3Shift,
+    /// The ISO level 5 shift key.
+    IsoLevel5Shift,
 }
 
 /// An event fired by a key pressed by the user.
@@ -46,13 +169,6 @@ pub struct KeyEvent {
     pub shift: bool,
 }
 
-impl KeyEvent {
-    /// A dummy key event, with dummy data.
-    fn dummy() -> Self {
-        Self { main_key: Key::Esc, ctrl: false, alt: false, shift: false }
-    }
-}
-
 /// An event fired by a resize of the screen.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ResizeEvent {
@@ -62,20 +178,74 @@ pub struct ResizeEvent {
     pub size: Option<Vec2>,
 }
 
-impl ResizeEvent {
-    /// A dummy resize event, with dummy data.
-    fn dummy() -> Self {
-        Self { size: None }
-    }
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    /// The left mouse button.
+    Left,
+    /// The right mouse button.
+    Right,
+    /// The middle mouse button (often the wheel).
+    Middle,
 }
 
-/// A generic event type.
+/// The kind of a mouse event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseKind {
+    /// A mouse button was pressed.
+    Press(MouseButton),
+    /// A mouse button was released. Some terminals do not report which button
+    /// was released, in which case [`MouseButton::Left`] is reported.
+    Release(MouseButton),
+    /// The mouse moved while the given button was pressed.
+    Drag(MouseButton),
+    /// The mouse moved while no button was pressed.
+    Move,
+    /// The wheel was scrolled up (away from the user).
+    ScrollUp,
+    /// The wheel was scrolled down (towards the user).
+    ScrollDown,
+    /// The wheel was scrolled left (mostly on touchpads).
+    ScrollLeft,
+    /// The wheel was scrolled right (mostly on touchpads).
+    ScrollRight,
+}
+
+/// An event fired by the mouse. Only fired if mouse capture is enabled, see
+/// [`Builder::mouse_capture`](crate::terminal::Builder::mouse_capture).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    /// What the mouse did.
+    pub kind: MouseKind,
+    /// Position of the mouse in the screen when the event happened.
+    pub pos: Vec2,
+    /// Whether control is modifiying the mouse event (pressed).
+    pub ctrl: bool,
+    /// Whether alt is modifiying the mouse event (pressed).
+    pub alt: bool,
+    /// Whether shift is modifiying the mouse event (pressed).
+    pub shift: bool,
+}
+
+/// A generic event type.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     /// User resized screen.
     Resize(ResizeEvent),
     /// User pressed key.
     Key(KeyEvent),
+    /// User used the mouse.
+    Mouse(MouseEvent),
+    /// User pasted text. Only reported when bracketed paste is enabled;
+    /// control characters are replaced just like in
+    /// [`TermString::new_lossy`].
+    Paste(TermString),
+    /// Terminal gained or lost focus. Only reported when focus reporting is
+    /// enabled.
+    Focus {
+        /// Whether the focus was gained (`true`) or lost (`false`).
+        gained: bool,
+    },
 }
 
 impl From<ResizeEvent> for Event {
@@ -90,63 +260,146 @@ impl From<KeyEvent> for Event {
     }
 }
 
-/// Snapshot of an event with a given epoch.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Snapshot<E> {
-    /// The event of this snapshot.
-    event: E,
-    /// Epoch count of this snapshot.
+impl From<MouseEvent> for Event {
+    fn from(event: MouseEvent) -> Self {
+        Event::Mouse(event)
+    }
+}
+
+impl From<TermString> for Event {
+    fn from(text: TermString) -> Self {
+        Event::Paste(text)
+    }
+}
+
+/// What the event channel does when an event arrives and the channel's queue
+/// is full, or how it stores events in general.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    /// Discards the oldest unread event in order to make room for the new
+    /// one. This is the default.
+    #[default]
+    DropOldest,
+    /// Discards the new event, keeping the unread ones.
+    DropNewest,
+    /// Keeps only the newest event of each kind (e.g. the last key and the
+    /// last resize), regardless of the capacity. Events of the same kind
+    /// arriving before the application reads them replace each other.
+    KeepLatest,
+}
+
+/// An event in the channel's queue, paired with its epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// The event of this entry.
+    event: Event,
+    /// Epoch count of this entry.
     epoch: Epoch,
 }
 
 /// Channel's data that needs locking.
 #[derive(Debug)]
 struct ChannelData {
-    /// Last key event's snapshot.
-    key: Snapshot<KeyEvent>,
-    /// Last resize event's snapshot.
-    resize: Snapshot<ResizeEvent>,
+    /// Queue of events not yet read by every reader, ordered by epoch.
+    queue: VecDeque<Entry>,
+    /// Maximum number of events in the queue.
+    capacity: usize,
+    /// Policy applied when the queue is full.
+    overflow: Overflow,
+    /// Epoch of the last written event.
+    last: Epoch,
+    /// Number of readers (terminal handles) at each epoch.
+    readers: BTreeMap<Epoch, usize>,
 }
 
-impl Default for ChannelData {
-    fn default() -> Self {
+impl ChannelData {
+    /// Creates channel data with the given capacity and overflow policy. A
+    /// capacity of zero is treated as one.
+    fn new(capacity: usize, overflow: Overflow) -> Self {
+        let capacity = capacity.max(1);
         Self {
-            key: Snapshot { event: KeyEvent::dummy(), epoch: 0 },
-            resize: Snapshot { event: ResizeEvent::dummy(), epoch: 0 },
+            queue: VecDeque::with_capacity(capacity.min(64)),
+            capacity,
+            overflow,
+            last: 0,
+            readers: BTreeMap::new(),
         }
     }
-}
 
-impl ChannelData {
     /// Selects maximum last epoch.
     fn epoch(&self) -> Epoch {
-        self.key.epoch.max(self.resize.epoch)
+        self.last
     }
 
-    /// Reads the correct lastest event, given the epoch where the caller is.
-    /// Uses the adequate event, i.e. resize event has precedence.
+    /// Reads the oldest unread event, given the epoch where the caller is.
     fn read(&self, epoch: Epoch) -> Option<(Epoch, Event)> {
-        if self.resize.epoch > epoch {
-            Some((self.resize.epoch, self.resize.event.into()))
-        } else if self.key.epoch > epoch {
-            Some((self.key.epoch, self.key.event.into()))
-        } else {
-            None
+        self.queue
+            .iter()
+            .find(|entry| entry.epoch > epoch)
+            .map(|entry| (entry.epoch, entry.event.clone()))
+    }
+
+    /// Adds a reader at the given epoch. Events after it are kept until the
+    /// reader reads them or is removed.
+    fn add_reader(&mut self, epoch: Epoch) {
+        *self.readers.entry(epoch).or_insert(0) += 1;
+    }
+
+    /// Removes a reader at the given epoch, discarding the events every
+    /// remaining reader has read.
+    fn remove_reader(&mut self, epoch: Epoch) {
+        if let Some(count) = self.readers.get_mut(&epoch) {
+            *count -= 1;
+            if *count == 0 {
+                self.readers.remove(&epoch);
+            }
         }
+        self.discard_read();
     }
 
-    /// Writes an event into the channel. Advances current epoch.
-    fn write(&mut self, event: Event) {
-        let epoch = self.epoch();
+    /// Moves a reader from the epoch where it was to the given epoch, marking
+    /// the events in between as read by it. Events are discarded once every
+    /// reader has read them.
+    fn mark_read(&mut self, prev: Epoch, epoch: Epoch) {
+        self.add_reader(epoch);
+        self.remove_reader(prev);
+    }
 
-        match event {
-            Event::Key(event) => {
-                self.key = Snapshot { epoch: epoch + 1, event }
+    /// Discards the events read by every reader.
+    fn discard_read(&mut self) {
+        let read = self.readers.keys().next().copied().unwrap_or(self.last);
+        while let Some(entry) = self.queue.front() {
+            if entry.epoch > read {
+                break;
+            }
+            self.queue.pop_front();
+        }
+    }
+
+    /// Writes an event into the channel. Advances current epoch and returns
+    /// the epoch of the event, unless the event is discarded.
+    fn write(&mut self, event: Event) -> Option<Epoch> {
+        match self.overflow {
+            Overflow::DropOldest => {
+                if self.queue.len() >= self.capacity {
+                    self.queue.pop_front();
+                }
             },
-            Event::Resize(event) => {
-                self.resize = Snapshot { epoch: epoch + 1, event }
+            Overflow::DropNewest => {
+                if self.queue.len() >= self.capacity {
+                    return None;
+                }
+            },
+            Overflow::KeepLatest => {
+                let kind = mem::discriminant(&event);
+                self.queue
+                    .retain(|entry| mem::discriminant(&entry.event) != kind);
             },
         }
+
+        self.last += 1;
+        self.queue.push_back(Entry { event, epoch: self.last });
+        Some(self.last)
     }
 }
 
@@ -159,24 +412,24 @@ pub(crate) struct Channel {
     notifier: Notify,
 }
 
-impl Default for Channel {
-    fn default() -> Self {
+impl Channel {
+    /// Creates a channel with the given queue capacity and overflow policy.
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
         Channel {
-            data: Mutex::new(ChannelData::default()),
+            data: Mutex::new(ChannelData::new(capacity, overflow)),
             notifier: Notify::new(),
         }
     }
-}
 
-impl Channel {
     /// Notifies all parties subscribed to the channel.
     pub fn notify(&self) {
         self.notifier.notify_waiters()
     }
 
-    /// Subscribes to changes in this channel.
-    pub async fn subscribe(&self) {
-        self.notifier.notified().await
+    /// Subscribes to changes in this channel. The subscription starts when
+    /// this method is called, not when the returned future is first polled.
+    pub fn subscribe(&self) -> Notified<'_> {
+        self.notifier.notified()
     }
 
     /// Selects maximum last epoch.
@@ -184,14 +437,33 @@ impl Channel {
         self.data.lock().unwrap().epoch()
     }
 
-    /// Reads the correct lastest event, given the epoch where the caller is.
-    /// Uses the adequate event, i.e. resize event has precedence.
+    /// Reads the oldest unread event, given the epoch where the caller is.
     pub fn read(&self, epoch: Epoch) -> Option<(Epoch, Event)> {
         self.data.lock().unwrap().read(epoch)
     }
 
-    /// Writes an event into the channel. Advances current epoch.
-    pub fn write(&self, event: Event) {
+    /// Adds a reader at the given epoch. Events after it are kept until the
+    /// reader reads them or is removed.
+    pub fn add_reader(&self, epoch: Epoch) {
+        self.data.lock().unwrap().add_reader(epoch)
+    }
+
+    /// Removes a reader at the given epoch, discarding the events every
+    /// remaining reader has read.
+    pub fn remove_reader(&self, epoch: Epoch) {
+        self.data.lock().unwrap().remove_reader(epoch)
+    }
+
+    /// Moves a reader from the epoch where it was to the given epoch, marking
+    /// the events in between as read by it. Events are discarded once every
+    /// reader has read them.
+    pub fn mark_read(&self, prev: Epoch, epoch: Epoch) {
+        self.data.lock().unwrap().mark_read(prev, epoch)
+    }
+
+    /// Writes an event into the channel. Advances current epoch and returns
+    /// the epoch of the event, unless the event is discarded.
+    pub fn write(&self, event: Event) -> Option<Epoch> {
         self.data.lock().unwrap().write(event)
     }
 }
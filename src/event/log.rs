@@ -0,0 +1,541 @@
+//! This module defines event logs: a record of the events delivered to an
+//! application, which can be replayed later.
+//!
+//! An event log is a text file with one event per line, each line made of
+//! the time in microseconds since the event reactor started, the epoch of the
+//! event channel after the event was written, the frame of the fixed frame
+//! clock when the event was delivered (0 if not used), and the event itself.
+//! Empty lines and lines starting with `#` are ignored. For instance:
+//!
+//! ```text
+//! # andiskaz event log
+//! 153204 1 8 key char:61 ---
+//! 402117 2 21 key up c--
+//! 998001 3 50 resize 100 30
+//! 1203444 4 61 mouse press:left 10 4 --s
+//! 1500000 5 75 paste hello\nworld
+//! 1700000 6 85 focus lost
+//! ```
+
+use crate::{
+    coord::{Coord, Vec2},
+    error::{BadEventLog, Error},
+    event::{
+        Epoch,
+        Event,
+        Key,
+        KeyEvent,
+        MediaKey,
+        ModifierKey,
+        MouseButton,
+        MouseEvent,
+        MouseKind,
+        ResizeEvent,
+    },
+    recording::Sink,
+    string::TermString,
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{Read, Write},
+    time::Duration,
+};
+
+/// Names of keys without data.
+const KEY_NAMES: [(Key, &str); 23] = [
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Esc, "esc"),
+    (Key::Enter, "enter"),
+    (Key::Backspace, "backspace"),
+    (Key::Tab, "tab"),
+    (Key::BackTab, "backtab"),
+    (Key::Delete, "delete"),
+    (Key::Insert, "insert"),
+    (Key::Home, "home"),
+    (Key::End, "end"),
+    (Key::PageUp, "pageup"),
+    (Key::PageDown, "pagedown"),
+    (Key::Null, "null"),
+    (Key::CapsLock, "capslock"),
+    (Key::ScrollLock, "scrolllock"),
+    (Key::NumLock, "numlock"),
+    (Key::PrintScreen, "printscreen"),
+    (Key::Pause, "pause"),
+    (Key::Menu, "menu"),
+    (Key::KeypadBegin, "keypadbegin"),
+];
+
+/// Names of media keys.
+const MEDIA_NAMES: [(MediaKey, &str); 13] = [
+    (MediaKey::Play, "play"),
+    (MediaKey::Pause, "pause"),
+    (MediaKey::PlayPause, "playpause"),
+    (MediaKey::Reverse, "reverse"),
+    (MediaKey::Stop, "stop"),
+    (MediaKey::FastForward, "fastforward"),
+    (MediaKey::Rewind, "rewind"),
+    (MediaKey::TrackNext, "tracknext"),
+    (MediaKey::TrackPrevious, "trackprevious"),
+    (MediaKey::Record, "record"),
+    (MediaKey::LowerVolume, "lowervolume"),
+    (MediaKey::RaiseVolume, "raisevolume"),
+    (MediaKey::MuteVolume, "mutevolume"),
+];
+
+/// Names of modifier keys.
+const MODIFIER_NAMES: [(ModifierKey, &str); 14] = [
+    (ModifierKey::LeftShift, "leftshift"),
+    (ModifierKey::LeftControl, "leftcontrol"),
+    (ModifierKey::LeftAlt, "leftalt"),
+    (ModifierKey::LeftSuper, "leftsuper"),
+    (ModifierKey::LeftHyper, "lefthyper"),
+    (ModifierKey::LeftMeta, "leftmeta"),
+    (ModifierKey::RightShift, "rightshift"),
+    (ModifierKey::RightControl, "rightcontrol"),
+    (ModifierKey::RightAlt, "rightalt"),
+    (ModifierKey::RightSuper, "rightsuper"),
+    (ModifierKey::RightHyper, "righthyper"),
+    (ModifierKey::RightMeta, "rightmeta"),
+    (ModifierKey::IsoLevel3Shift, "isolevel3shift"),
+    (ModifierKey::IsoLevel5Shift, "isolevel5shift"),
+];
+
+/// Names of mouse buttons.
+const BUTTON_NAMES: [(MouseButton, &str); 3] = [
+    (MouseButton::Left, "left"),
+    (MouseButton::Right, "right"),
+    (MouseButton::Middle, "middle"),
+];
+
+/// Names of mouse event kinds without data.
+const MOUSE_NAMES: [(MouseKind, &str); 5] = [
+    (MouseKind::Move, "move"),
+    (MouseKind::ScrollUp, "scrollup"),
+    (MouseKind::ScrollDown, "scrolldown"),
+    (MouseKind::ScrollLeft, "scrollleft"),
+    (MouseKind::ScrollRight, "scrollright"),
+];
+
+/// Finds the name of a value in a table of names.
+fn name_of<T>(table: &[(T, &'static str)], value: T) -> &'static str
+where
+    T: PartialEq,
+{
+    table
+        .iter()
+        .find(|(entry, _)| *entry == value)
+        .map(|(_, name)| *name)
+        .expect("Event log name table is incomplete")
+}
+
+/// Finds the value of a name in a table of names.
+fn value_of<T>(table: &[(T, &'static str)], name: &str) -> Option<T>
+where
+    T: Copy,
+{
+    table.iter().find(|(_, entry)| *entry == name).map(|(value, _)| *value)
+}
+
+/// A single entry of an [`EventLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Time since the event reactor started when the event was delivered.
+    pub time: Duration,
+    /// Epoch of the event channel after the event was written.
+    pub epoch: Epoch,
+    /// Frame of the fixed frame clock when the event was delivered, or 0 if
+    /// the clock was not used (see
+    /// [`Builder::frame_clock`](crate::terminal::Builder::frame_clock)).
+    pub frame: u64,
+    /// The event delivered.
+    pub event: Event,
+}
+
+impl LogEntry {
+    /// Parses an entry from a (non-empty, non-comment) line of a log.
+    fn parse(line: &str) -> Option<Self> {
+        let (time, rest) = line.split_once(' ')?;
+        let (epoch, rest) = rest.split_once(' ')?;
+        let (frame, event) = rest.split_once(' ')?;
+        Some(Self {
+            time: Duration::from_micros(time.parse().ok()?),
+            epoch: epoch.parse().ok()?,
+            frame: frame.parse().ok()?,
+            event: parse_event(event)?,
+        })
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "{} {} {} ",
+            self.time.as_micros(),
+            self.epoch,
+            self.frame
+        )?;
+        write_event(fmtr, &self.event)
+    }
+}
+
+/// A log of events delivered to an application, as recorded through
+/// [`Builder::record_events`](crate::terminal::Builder::record_events), and
+/// replayed through
+/// [`Builder::replay_events`](crate::terminal::Builder::replay_events).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventLog {
+    /// Entries of the log, ordered by time.
+    entries: Vec<LogEntry>,
+}
+
+impl EventLog {
+    /// Creates an event log from the given entries, ordered by time.
+    pub fn new(entries: Vec<LogEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Parses an event log from its text.
+    pub fn parse(text: &str) -> Result<Self, BadEventLog> {
+        let mut entries = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match LogEntry::parse(line) {
+                Some(entry) => entries.push(entry),
+                None => Err(BadEventLog { line: index + 1 })?,
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// Reads and parses an event log from the given reader, e.g. a file.
+    pub fn read<R>(mut reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Ok(Self::parse(&text)?)
+    }
+
+    /// The entries of this log, ordered by time.
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+}
+
+impl fmt::Display for EventLog {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(fmtr, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes delivered events into an event log. Logging is best-effort: the log
+/// is written in the background, and errors only stop the logging.
+#[derive(Debug)]
+pub(crate) struct EventRecorder {
+    /// Where the log is written to.
+    sink: Sink,
+}
+
+impl EventRecorder {
+    /// Creates a recorder that writes to the given writer.
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self { sink: Sink::new("event log", writer) }
+    }
+
+    /// Starts a log by writing its header.
+    pub fn start(&self) {
+        self.sink.start();
+        self.sink.write(b"# andiskaz event log\n".to_vec());
+    }
+
+    /// Records a delivered event.
+    pub fn record(
+        &self,
+        time: Duration,
+        epoch: Epoch,
+        frame: u64,
+        event: &Event,
+    ) {
+        let entry = LogEntry { time, epoch, frame, event: event.clone() };
+        self.sink.write(format!("{}\n", entry).into_bytes());
+    }
+
+    /// Finishes the log, waiting for it to be written and flushed.
+    pub fn finish(&self) {
+        self.sink.finish();
+    }
+}
+
+/// Events of a log being replayed.
+#[derive(Debug, Clone)]
+pub(crate) struct Replay {
+    /// Entries not replayed yet.
+    entries: VecDeque<LogEntry>,
+}
+
+impl Replay {
+    /// Starts replaying the given log.
+    pub fn new(log: &EventLog) -> Self {
+        Self { entries: log.entries.iter().cloned().collect() }
+    }
+
+    /// Whether every event has been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Time when the next event is due, if any.
+    pub fn next_time(&self) -> Option<Duration> {
+        self.entries.front().map(|entry| entry.time)
+    }
+
+    /// Takes the next event if it is due at the given time, or, with the
+    /// fixed frame clock, at the given frame instead.
+    pub fn pop_due(
+        &mut self,
+        now: Duration,
+        frame: Option<u64>,
+    ) -> Option<Event> {
+        let entry = self.entries.front()?;
+        let due = match frame {
+            Some(frame) => entry.frame <= frame,
+            None => entry.time <= now,
+        };
+        if due {
+            self.entries.pop_front().map(|entry| entry.event)
+        } else {
+            None
+        }
+    }
+}
+
+/// Writes modifier flags as three characters, `c`, `a` and `s`, or `-` when
+/// unset.
+fn write_modifiers<W>(
+    out: &mut W,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    out.write_char(if ctrl { 'c' } else { '-' })?;
+    out.write_char(if alt { 'a' } else { '-' })?;
+    out.write_char(if shift { 's' } else { '-' })
+}
+
+/// Parses modifier flags (see [`write_modifiers`]).
+fn parse_modifiers(token: &str) -> Option<(bool, bool, bool)> {
+    let mut chars = token.chars();
+    let mut flag = |set| match chars.next()? {
+        '-' => Some(false),
+        ch if ch == set => Some(true),
+        _ => None,
+    };
+    let flags = (flag('c')?, flag('a')?, flag('s')?);
+    if chars.next().is_none() {
+        Some(flags)
+    } else {
+        None
+    }
+}
+
+/// Writes a key as a single token.
+fn write_key<W>(out: &mut W, key: Key) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    match key {
+        Key::Char(ch) => write!(out, "char:{:x}", u32::from(ch)),
+        Key::F(number) => write!(out, "f{}", number),
+        Key::Media(media) => {
+            write!(out, "media:{}", name_of(&MEDIA_NAMES, media))
+        },
+        Key::Modifier(modifier) => {
+            write!(out, "modifier:{}", name_of(&MODIFIER_NAMES, modifier))
+        },
+        _ => out.write_str(name_of(&KEY_NAMES, key)),
+    }
+}
+
+/// Parses a key token (see [`write_key`]).
+fn parse_key(token: &str) -> Option<Key> {
+    if let Some(code) = token.strip_prefix("char:") {
+        let code = u32::from_str_radix(code, 16).ok()?;
+        char::from_u32(code).map(Key::Char)
+    } else if let Some(name) = token.strip_prefix("media:") {
+        value_of(&MEDIA_NAMES, name).map(Key::Media)
+    } else if let Some(name) = token.strip_prefix("modifier:") {
+        value_of(&MODIFIER_NAMES, name).map(Key::Modifier)
+    } else if let Some(number) = token.strip_prefix('f') {
+        number.parse().ok().map(Key::F)
+    } else {
+        value_of(&KEY_NAMES, token)
+    }
+}
+
+/// Writes a mouse event kind as a single token.
+fn write_mouse_kind<W>(out: &mut W, kind: MouseKind) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    match kind {
+        MouseKind::Press(button) => {
+            write!(out, "press:{}", name_of(&BUTTON_NAMES, button))
+        },
+        MouseKind::Release(button) => {
+            write!(out, "release:{}", name_of(&BUTTON_NAMES, button))
+        },
+        MouseKind::Drag(button) => {
+            write!(out, "drag:{}", name_of(&BUTTON_NAMES, button))
+        },
+        _ => out.write_str(name_of(&MOUSE_NAMES, kind)),
+    }
+}
+
+/// Parses a mouse event kind token (see [`write_mouse_kind`]).
+fn parse_mouse_kind(token: &str) -> Option<MouseKind> {
+    if let Some(name) = token.strip_prefix("press:") {
+        value_of(&BUTTON_NAMES, name).map(MouseKind::Press)
+    } else if let Some(name) = token.strip_prefix("release:") {
+        value_of(&BUTTON_NAMES, name).map(MouseKind::Release)
+    } else if let Some(name) = token.strip_prefix("drag:") {
+        value_of(&BUTTON_NAMES, name).map(MouseKind::Drag)
+    } else {
+        value_of(&MOUSE_NAMES, token)
+    }
+}
+
+/// Writes pasted text, escaping backslashes and control characters so that
+/// the text fits in a single line.
+fn write_text<W>(out: &mut W, text: &str) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    for ch in text.chars() {
+        match ch {
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            _ if ch.is_control() => write!(out, "\\u{{{:x}}}", u32::from(ch))?,
+            _ => out.write_char(ch)?,
+        }
+    }
+    Ok(())
+}
+
+/// Parses pasted text (see [`write_text`]).
+fn parse_text(escaped: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut chars = escaped.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            text.push(ch);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => text.push('\\'),
+            'n' => text.push('\n'),
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{')?;
+                let (code, rest) = rest.split_once('}')?;
+                text.push(char::from_u32(u32::from_str_radix(code, 16).ok()?)?);
+                chars = rest.chars();
+            },
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+/// Writes an event in the log format.
+fn write_event<W>(out: &mut W, event: &Event) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    match event {
+        Event::Resize(ResizeEvent { size: Some(size) }) => {
+            write!(out, "resize {} {}", size.x, size.y)
+        },
+        Event::Resize(ResizeEvent { size: None }) => {
+            out.write_str("resize none")
+        },
+        Event::Key(key) => {
+            out.write_str("key ")?;
+            write_key(out, key.main_key)?;
+            out.write_char(' ')?;
+            write_modifiers(out, key.ctrl, key.alt, key.shift)
+        },
+        Event::Mouse(mouse) => {
+            out.write_str("mouse ")?;
+            write_mouse_kind(out, mouse.kind)?;
+            write!(out, " {} {} ", mouse.pos.x, mouse.pos.y)?;
+            write_modifiers(out, mouse.ctrl, mouse.alt, mouse.shift)
+        },
+        Event::Paste(text) => {
+            out.write_str("paste ")?;
+            write_text(out, text)
+        },
+        Event::Focus { gained } => {
+            write!(out, "focus {}", if *gained { "gained" } else { "lost" })
+        },
+    }
+}
+
+/// Parses an event in the log format (see [`write_event`]).
+fn parse_event(text: &str) -> Option<Event> {
+    let (kind, rest) = text.split_once(' ')?;
+    let event = match kind {
+        "resize" if rest == "none" => Event::Resize(ResizeEvent { size: None }),
+        "resize" => {
+            let (x, y) = rest.split_once(' ')?;
+            let size = Vec2 { x: parse_coord(x)?, y: parse_coord(y)? };
+            Event::Resize(ResizeEvent { size: Some(size) })
+        },
+        "key" => {
+            let (key, modifiers) = rest.split_once(' ')?;
+            let (ctrl, alt, shift) = parse_modifiers(modifiers)?;
+            Event::Key(KeyEvent { main_key: parse_key(key)?, ctrl, alt, shift })
+        },
+        "mouse" => {
+            let mut tokens = rest.split(' ');
+            let kind = parse_mouse_kind(tokens.next()?)?;
+            let x = parse_coord(tokens.next()?)?;
+            let y = parse_coord(tokens.next()?)?;
+            let (ctrl, alt, shift) = parse_modifiers(tokens.next()?)?;
+            if tokens.next().is_some() {
+                return None;
+            }
+            let pos = Vec2 { x, y };
+            Event::Mouse(MouseEvent { kind, pos, ctrl, alt, shift })
+        },
+        "paste" => Event::Paste(TermString::new_lossy(parse_text(rest)?)),
+        "focus" => match rest {
+            "gained" => Event::Focus { gained: true },
+            "lost" => Event::Focus { gained: false },
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Parses a coordinate.
+fn parse_coord(token: &str) -> Option<Coord> {
+    token.parse().ok()
+}
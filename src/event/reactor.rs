@@ -3,16 +3,18 @@
 use crate::{
     coord::Vec2,
     error::Error,
-    event::{Event, Key, KeyEvent, ResizeEvent},
+    event::{Event, Key, KeyEvent, Replay, ResizeEvent},
+    screen::FrameClock,
     stdio::LockedStdout,
     terminal::Shared,
 };
-use crossterm::event::{Event as CrosstermEvent, KeyCode as CrosstermKey};
-use std::time::Duration;
-use tokio::{task, time};
+use std::{collections::VecDeque, future, time::Duration};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::{task, time, time::Instant};
 
-/// Event reactor: gets events from the low-level (OS + Crossterm), and sends
-/// them to the events channel.
+/// Event reactor: gets events from the low-level (the terminal's backend), and
+/// sends them to the events channel.
 #[derive(Debug)]
 pub(crate) struct Reactor<'shared> {
     /// Shared data between every party of the application.
@@ -20,13 +22,34 @@ pub(crate) struct Reactor<'shared> {
     /// A guard to the standard output, to prevent renderer from rendering if
     /// invalid size.
     stdout_guard: Option<LockedStdout<'shared>>,
+    /// When the reactor started, the origin of logged and replayed times.
+    start: Instant,
+    /// Events being replayed instead of the backend's, if any.
+    replay: Option<Replay>,
+    /// Last frame of the fixed frame clock whose events were delivered.
+    frame: u64,
+    /// Events held until the next tick of the fixed frame clock.
+    held: VecDeque<Event>,
+    /// Signal of the process being continued after stopped (`SIGCONT`), if
+    /// listened to.
+    #[cfg(unix)]
+    continued: Option<Signal>,
 }
 
 impl<'shared> Reactor<'shared> {
     /// Constructs the reactor from a reference to shared data on which it will
     /// place the events.
     pub fn new(shared: &'shared Shared) -> Self {
-        Self { shared, stdout_guard: None }
+        Self {
+            shared,
+            stdout_guard: None,
+            start: Instant::now(),
+            replay: shared.replay().map(Replay::new),
+            frame: 0,
+            held: VecDeque::new(),
+            #[cfg(unix)]
+            continued: None,
+        }
     }
 
     /// Returns whether the current screen size is valid.
@@ -37,6 +60,14 @@ impl<'shared> Reactor<'shared> {
     /// Executes the pre-"reactor loop" functions, handling the initial screen
     /// size and correctly dealing with the fact that it is invalid, if it is.
     pub async fn pre_loop(&mut self, initial_size: Vec2) -> Result<(), Error> {
+        if let Some(recorder) = self.shared.event_recorder() {
+            recorder.start();
+        }
+        #[cfg(unix)]
+        if self.shared.screen().listens_continued() {
+            let kind = SignalKind::from_raw(libc::SIGCONT);
+            self.continued = Some(signal(kind)?);
+        }
         let mut screen = self.shared.screen().lock().await;
         let min_size = screen.min_size();
         if initial_size.x < min_size.x || initial_size.y < min_size.y {
@@ -56,11 +87,39 @@ impl<'shared> Reactor<'shared> {
         let mut interval = time::interval(event_interval);
 
         while self.shared.is_connected() {
-            match self.poll().await? {
-                Some(crossterm) => self.react(crossterm).await?,
-                None => tokio::select! {
-                    _ = interval.tick() => (),
-                    _ = self.shared.events().subscribe() => (),
+            // Polling happens under the guard as well, so that no input is
+            // taken while the application owns the terminal (e.g. while an
+            // external program runs).
+            let guard = self.shared.service_guard().await?;
+            let clock = self.shared.screen().clock();
+            match self.poll()? {
+                Some(event) if clock.is_some() => self.held.push_back(event),
+                Some(event) => self.react(event).await?,
+                None => {
+                    drop(guard);
+                    let due = self.replay_due();
+                    let mut continued = false;
+                    let mut ticked = false;
+                    tokio::select! {
+                        _ = interval.tick() => (),
+                        _ = self.shared.events().subscribe() => (),
+                        _ = time::sleep_until(due.unwrap_or(self.start)),
+                            if due.is_some() => (),
+                        _ = self.continued() => continued = true,
+                        _ = tick_notified(clock) => ticked = true,
+                    }
+                    if let Some(clock) = clock.filter(|_| ticked) {
+                        let _guard = self.shared.service_guard().await?;
+                        self.deliver_frames(clock).await?;
+                    }
+                    // A suspension started here already set the terminal up
+                    // again.
+                    let external = continued
+                        && !self.shared.screen().take_stopped_itself();
+                    if external {
+                        let _guard = self.shared.service_guard().await?;
+                        self.shared.screen().lock().await.resume().await?;
+                    }
                 },
             }
         }
@@ -68,30 +127,12 @@ impl<'shared> Reactor<'shared> {
         Ok(())
     }
 
-    /// Reacts to a single event. Input uses the crossterm encoding for the
-    /// event.
-    async fn react(&mut self, crossterm: CrosstermEvent) -> Result<(), Error> {
-        let _guard = self.shared.service_guard().await?;
-
-        match crossterm {
-            CrosstermEvent::Key(key) => {
-                let maybe_key = key_from_crossterm(key.code);
-                if let Some(main_key) = maybe_key {
-                    use crossterm::event::KeyModifiers as Mod;
-
-                    let evt = KeyEvent {
-                        main_key,
-                        ctrl: key.modifiers.intersects(Mod::CONTROL),
-                        alt: key.modifiers.intersects(Mod::ALT),
-                        shift: key.modifiers.intersects(Mod::SHIFT),
-                    };
-
-                    self.send(Event::Key(evt));
-                }
-            },
-
-            CrosstermEvent::Resize(width, height) => {
-                let size = Vec2 { x: width, y: height };
+    /// Reacts to a single event, as reported by the backend. Must be called
+    /// with the service guard acquired.
+    async fn react(&mut self, event: Event) -> Result<(), Error> {
+        match event {
+            Event::Resize(ResizeEvent { size: Some(size) }) => {
+                let size = self.shared.screen().viewport(size);
                 let mut locked_screen = self.shared.screen().lock().await;
                 let prev_size_valid = self.is_size_valid();
                 locked_screen
@@ -107,48 +148,133 @@ impl<'shared> Reactor<'shared> {
                 }
             },
 
-            _ => (),
+            // Backends always report the actual size, but replayed logs have
+            // invalid sizes as they were delivered.
+            Event::Resize(ResizeEvent { size: None }) => {
+                if self.replay.is_some() {
+                    self.send(event);
+                }
+            },
+
+            Event::Key(KeyEvent {
+                main_key: Key::Char('z'),
+                ctrl: true,
+                alt: false,
+                shift: false,
+            }) if self.shared.suspends_on_ctrl_z() => {
+                self.shared.screen().lock().await.suspend().await?;
+            },
+
+            Event::Focus { gained } => {
+                self.shared.screen().set_focused(gained);
+                self.send(event);
+            },
+
+            _ => self.send(event),
         }
 
         Ok(())
     }
 
-    /// Polls for a single event from the OS.
-    async fn poll<'guard>(&self) -> Result<Option<CrosstermEvent>, Error> {
-        let result = task::block_in_place(|| {
-            match crossterm::event::poll(Duration::from_millis(0))? {
-                true => crossterm::event::read().map(Some),
-                false => Ok(None),
+    /// Delivers the events of every frame ticked by the fixed frame clock
+    /// since the last call: at each frame, the replayed events due, and then
+    /// the events held since the last tick. Must be called with the service
+    /// guard acquired.
+    async fn deliver_frames(
+        &mut self,
+        clock: &FrameClock,
+    ) -> Result<(), Error> {
+        let ticked = clock.ticked();
+        while self.frame < ticked {
+            self.frame += 1;
+            let frame = self.frame;
+            let now = self.start.elapsed();
+            while let Some(event) = self
+                .replay
+                .as_mut()
+                .and_then(|replay| replay.pop_due(now, Some(frame)))
+            {
+                self.react(event).await?;
             }
-        });
+            while let Some(event) = self.held.pop_front() {
+                self.react(event).await?;
+            }
+            clock.make_ready(self.frame);
+        }
+        Ok(())
+    }
 
+    /// Polls for a single event from the backend, or from the log being
+    /// replayed, while there are events to be replayed. With the fixed frame
+    /// clock, replayed events are delivered at ticks instead.
+    fn poll(&mut self) -> Result<Option<Event>, Error> {
+        if let Some(replay) = &mut self.replay {
+            if !replay.is_finished() {
+                if self.shared.screen().clock().is_some() {
+                    return Ok(None);
+                }
+                return Ok(replay.pop_due(self.start.elapsed(), None));
+            }
+        }
+        let backend = self.shared.backend();
+        let result = task::block_in_place(|| backend.poll_event());
         result.map_err(Into::into)
     }
 
-    /// Sends an event through the channel, so that the listener receives it.
+    /// Waits until the process is continued after stopped, if listened to.
+    /// Otherwise, never finishes.
+    #[cfg(unix)]
+    async fn continued(&mut self) {
+        let received = match &mut self.continued {
+            Some(signal) => signal.recv().await.is_some(),
+            None => false,
+        };
+        if !received {
+            future::pending().await
+        }
+    }
+
+    /// Never finishes, since processes are not stopped outside of unix.
+    #[cfg(not(unix))]
+    async fn continued(&mut self) {
+        future::pending().await
+    }
+
+    /// When the next replayed event is due, if replaying by the wall clock.
+    fn replay_due(&self) -> Option<Instant> {
+        if self.shared.screen().clock().is_some() {
+            return None;
+        }
+        let replay = self.replay.as_ref()?;
+        replay.next_time().map(|time| self.start + time)
+    }
+
+    /// Sends an event through the channel, so that the listener receives it,
+    /// and logs it if recording and not discarded by the channel.
     fn send(&self, event: Event) {
-        self.shared.events().write(event);
+        if let Some(recorder) = self.shared.event_recorder() {
+            let time = self.start.elapsed();
+            if let Some(epoch) = self.shared.events().write(event.clone()) {
+                recorder.record(time, epoch, self.frame, &event);
+            }
+        } else {
+            self.shared.events().write(event);
+        }
         self.shared.events().notify();
     }
 }
 
-impl<'shared> Drop for Reactor<'shared> {
-    fn drop(&mut self) {
-        self.shared.disconnect();
+/// Waits until the fixed frame clock ticks, if used. Otherwise, never
+/// finishes.
+async fn tick_notified(clock: Option<&FrameClock>) {
+    match clock {
+        Some(clock) => clock.tick_notified().await,
+        None => future::pending().await,
     }
 }
 
-/// Translates a crossterm key to an Andiskaz key.
-fn key_from_crossterm(crossterm: CrosstermKey) -> Option<Key> {
-    match crossterm {
-        CrosstermKey::Esc => Some(Key::Esc),
-        CrosstermKey::Backspace => Some(Key::Backspace),
-        CrosstermKey::Enter => Some(Key::Enter),
-        CrosstermKey::Up => Some(Key::Up),
-        CrosstermKey::Down => Some(Key::Down),
-        CrosstermKey::Left => Some(Key::Left),
-        CrosstermKey::Right => Some(Key::Right),
-        CrosstermKey::Char(ch) => Some(Key::Char(ch)),
-        _ => None,
+impl<'shared> Drop for Reactor<'shared> {
+    fn drop(&mut self) {
+        self.shared.disconnect();
     }
 }
@@ -0,0 +1,216 @@
+use crate::{
+    coord::Vec2,
+    error::BadEventLog,
+    event::{
+        ChannelData,
+        Epoch,
+        Event,
+        EventLog,
+        Key,
+        KeyEvent,
+        LogEntry,
+        MediaKey,
+        ModifierKey,
+        MouseButton,
+        MouseEvent,
+        MouseKind,
+        Overflow,
+        ResizeEvent,
+    },
+};
+use std::time::Duration;
+
+fn key(ch: char) -> Event {
+    Event::Key(KeyEvent {
+        main_key: Key::Char(ch),
+        ctrl: false,
+        alt: false,
+        shift: false,
+    })
+}
+
+fn resize() -> Event {
+    Event::Resize(ResizeEvent { size: None })
+}
+
+fn channel(capacity: usize, overflow: Overflow) -> (ChannelData, Epoch) {
+    let mut data = ChannelData::new(capacity, overflow);
+    data.add_reader(0);
+    (data, 0)
+}
+
+fn read_all(data: &mut ChannelData, epoch: &mut Epoch) -> Vec<Event> {
+    let mut events = Vec::new();
+    while let Some((new_epoch, event)) = data.read(*epoch) {
+        data.mark_read(*epoch, new_epoch);
+        *epoch = new_epoch;
+        events.push(event);
+    }
+    events
+}
+
+#[test]
+fn queue_keeps_order() {
+    let (mut data, mut epoch) = channel(8, Overflow::DropOldest);
+    data.write(key('a'));
+    data.write(resize());
+    data.write(key('b'));
+    assert_eq!(
+        read_all(&mut data, &mut epoch),
+        vec![key('a'), resize(), key('b')]
+    );
+    assert_eq!(data.epoch(), 3);
+    assert_eq!(data.read(3), None);
+}
+
+#[test]
+fn queue_drop_oldest() {
+    let (mut data, mut epoch) = channel(2, Overflow::DropOldest);
+    data.write(key('a'));
+    data.write(key('b'));
+    data.write(key('c'));
+    assert_eq!(read_all(&mut data, &mut epoch), vec![key('b'), key('c')]);
+}
+
+#[test]
+fn queue_drop_newest() {
+    let (mut data, mut epoch) = channel(2, Overflow::DropNewest);
+    assert_eq!(data.write(key('a')), Some(1));
+    assert_eq!(data.write(key('b')), Some(2));
+    assert_eq!(data.write(key('c')), None);
+    assert_eq!(read_all(&mut data, &mut epoch), vec![key('a'), key('b')]);
+    assert_eq!(data.write(key('d')), Some(3));
+    assert_eq!(read_all(&mut data, &mut epoch), vec![key('d')]);
+}
+
+#[test]
+fn queue_keep_latest() {
+    let (mut data, mut epoch) = channel(1, Overflow::KeepLatest);
+    data.write(key('a'));
+    data.write(resize());
+    data.write(key('b'));
+    assert_eq!(read_all(&mut data, &mut epoch), vec![resize(), key('b')]);
+}
+
+#[test]
+fn read_does_not_consume() {
+    let (mut data, mut epoch) = channel(4, Overflow::DropOldest);
+    data.write(key('a'));
+    data.write(key('b'));
+    assert_eq!(data.read(epoch), Some((1, key('a'))));
+    assert_eq!(data.read(epoch), Some((1, key('a'))));
+    data.mark_read(epoch, 1);
+    epoch = 1;
+    assert_eq!(data.read(epoch), Some((2, key('b'))));
+}
+
+#[test]
+fn events_kept_for_every_reader() {
+    let (mut data, mut first) = channel(4, Overflow::DropOldest);
+    data.add_reader(first);
+    let mut second = first;
+    data.write(key('a'));
+    data.write(key('b'));
+    assert_eq!(read_all(&mut data, &mut first), vec![key('a'), key('b')]);
+    data.write(key('c'));
+    assert_eq!(
+        read_all(&mut data, &mut second),
+        vec![key('a'), key('b'), key('c')]
+    );
+    data.write(key('d'));
+    data.remove_reader(second);
+    assert_eq!(read_all(&mut data, &mut first), vec![key('c'), key('d')]);
+    assert!(data.queue.is_empty());
+}
+
+#[test]
+fn paste_is_one_event() {
+    let (mut data, mut epoch) = channel(2, Overflow::DropOldest);
+    data.write(key('a'));
+    data.write(Event::Paste(tstring!["hello\nworld"]));
+    assert_eq!(
+        read_all(&mut data, &mut epoch),
+        vec![key('a'), Event::Paste(tstring!["hello�world"])]
+    );
+}
+
+#[test]
+fn log_round_trip() {
+    let modified =
+        |main_key| KeyEvent { main_key, ctrl: true, alt: false, shift: true };
+    let events = vec![
+        key('a'),
+        key(' '),
+        key('\\'),
+        Event::Key(modified(Key::F(12))),
+        Event::Key(modified(Key::Media(MediaKey::PlayPause))),
+        Event::Key(modified(Key::Modifier(ModifierKey::IsoLevel3Shift))),
+        Event::Key(modified(Key::PageDown)),
+        Event::Resize(ResizeEvent { size: Some(Vec2 { x: 100, y: 30 }) }),
+        resize(),
+        Event::Mouse(MouseEvent {
+            kind: MouseKind::Drag(MouseButton::Middle),
+            pos: Vec2 { x: 3, y: 7 },
+            ctrl: false,
+            alt: true,
+            shift: false,
+        }),
+        Event::Mouse(MouseEvent {
+            kind: MouseKind::ScrollLeft,
+            pos: Vec2 { x: 0, y: 0 },
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }),
+        Event::Paste(tstring!["back\\slash  spaces ç"]),
+        Event::Paste(tstring![""]),
+        Event::Focus { gained: false },
+    ];
+    let entries = events
+        .into_iter()
+        .enumerate()
+        .map(|(index, event)| LogEntry {
+            time: Duration::from_micros(index as u64 * 1500),
+            epoch: index as u128 + 1,
+            frame: index as u64 / 2,
+            event,
+        })
+        .collect();
+    let log = EventLog::new(entries);
+    let text = log.to_string();
+    assert_eq!(text.lines().count(), log.entries().len());
+    assert_eq!(EventLog::parse(&text), Ok(log));
+}
+
+#[test]
+fn log_format() {
+    let text = "# comment\n\n1500 1 0 key char:61 c--\n3000 2 3 resize none\n";
+    let log = EventLog::parse(text).unwrap();
+    let event = KeyEvent {
+        main_key: Key::Char('a'),
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+    assert_eq!(
+        log.entries(),
+        &[
+            LogEntry {
+                time: Duration::from_micros(1500),
+                epoch: 1,
+                frame: 0,
+                event: Event::Key(event),
+            },
+            LogEntry {
+                time: Duration::from_micros(3000),
+                epoch: 2,
+                frame: 3,
+                event: resize()
+            },
+        ]
+    );
+    assert_eq!(
+        EventLog::parse("1500 1 0 key char:61 c--\n3000 2 0 key sideways ---"),
+        Err(BadEventLog { line: 2 })
+    );
+}
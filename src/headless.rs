@@ -0,0 +1,286 @@
+//! This module provides a headless terminal, which runs an application against
+//! an in-memory screen instead of a real terminal. It is meant for testing:
+//! the application is fed a script of [`Event`]s, and the resulting screen can
+//! be inspected afterwards.
+//!
+//! The headless terminal uses the same machinery as a regular terminal (the
+//! event reactor, the event channel, the screen buffer and the renderer); only
+//! the [`Backend`] is replaced by an in-memory one, [`HeadlessBackend`]. Note
+//! that, just like a regular terminal, it requires a multi-threaded tokio
+//! runtime.
+//!
+//! # Example
+//! ```no_run
+//! use andiskaz::{
+//!     coord::Vec2,
+//!     event::{Event, Key, KeyEvent},
+//!     headless::Headless,
+//!     terminal::Terminal,
+//!     tstring,
+//! };
+//!
+//! # async fn example() -> Result<(), andiskaz::error::Error> {
+//! let esc = KeyEvent { main_key: Key::Esc, ctrl: false, alt: false, shift: false };
+//! let outcome = Headless::new(Vec2 { x: 80, y: 25 })
+//!     .event(esc)
+//!     .run(|mut term: Terminal| async move {
+//!         let mut session = term.lock_now().await?;
+//!         session.screen().styled_text(&tstring!["Hello!"], Default::default());
+//!         drop(session);
+//!         // Waits for ESC.
+//!         while !matches!(
+//!             term.listen().await?.event(),
+//!             Some(Event::Key(KeyEvent { main_key: Key::Esc, .. }))
+//!         ) {}
+//!         Ok::<_, andiskaz::error::Error>(())
+//!     })
+//!     .await?;
+//! outcome.value?;
+//! assert_eq!(&outcome.screen.row_text(0)[.. 6], "Hello!");
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    backend::{Backend, Output},
+    coord::Vec2,
+    error::Error,
+    event::{Event, ResizeEvent},
+    screen::Snapshot,
+    terminal::{Builder, Terminal},
+};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::{io::AsyncWrite, sync::Notify};
+
+/// A headless terminal harness: runs an application against an in-memory
+/// screen of a given size, feeding it a script of events.
+#[derive(Debug, Clone)]
+pub struct Headless {
+    /// Settings of the terminal. The backend is replaced when running.
+    builder: Builder,
+    /// The in-memory backend.
+    backend: HeadlessBackend,
+}
+
+impl Headless {
+    /// Creates a headless terminal with the given screen size and default
+    /// settings (see [`Builder`]), with the minimum screen size set to the
+    /// given size.
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            builder: Builder::new().min_screen(size),
+            backend: HeadlessBackend::new(size),
+        }
+    }
+
+    /// Replaces the settings of the terminal. The backend of the given builder
+    /// is ignored.
+    pub fn builder(self, builder: Builder) -> Self {
+        Self { builder, ..self }
+    }
+
+    /// Appends an event to the script. Resizes must carry the new size, e.g.
+    /// `ResizeEvent { size: Some(new_size) }`, and change the size of the
+    /// in-memory screen when they are delivered.
+    pub fn event<E>(self, event: E) -> Self
+    where
+        E: Into<Event>,
+    {
+        self.backend.push_event(event.into());
+        self
+    }
+
+    /// Appends every given event to the script, in order.
+    pub fn events<I>(self, events: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Event>,
+    {
+        for event in events {
+            self.backend.push_event(event.into());
+        }
+        self
+    }
+
+    /// Returns the in-memory backend used by this headless terminal.
+    pub fn backend(&self) -> &HeadlessBackend {
+        &self.backend
+    }
+
+    /// Runs the application. Scripted events are delivered as soon as
+    /// possible, in order; after the script is exhausted, no more events
+    /// arrive, so the application must finish on its own (e.g. when it reads
+    /// some scripted key).
+    ///
+    /// Returns the application's return value, together with the final state
+    /// of the screen.
+    pub async fn run<F, A, T>(self, start: F) -> Result<Outcome<T>, Error>
+    where
+        F: FnOnce(Terminal) -> A + Send + 'static,
+        A: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let builder = self.builder.backend(self.backend.clone());
+        let (value, shared) = builder.run_shared(start).await?;
+        let screen = shared.screen().lock().await.snapshot();
+        let output = self.backend.output_bytes();
+        Ok(Outcome { value, screen, output })
+    }
+}
+
+/// The result of running an application in a [`Headless`] terminal.
+#[derive(Debug, Clone)]
+pub struct Outcome<T> {
+    /// The value returned by the application.
+    pub value: T,
+    /// The final state of the screen, including changes not rendered yet.
+    pub screen: Snapshot,
+    /// Everything written to the terminal's output, i.e. the rendered
+    /// frames, encoded as ANSI escape sequences.
+    pub output: Vec<u8>,
+}
+
+/// State of the headless backend, shared by its copies.
+#[derive(Debug)]
+struct State {
+    /// Current size of the in-memory terminal.
+    size: Vec2,
+    /// Events not delivered yet.
+    script: VecDeque<Event>,
+    /// Everything written to the output.
+    output: Vec<u8>,
+}
+
+/// An in-memory [`Backend`], delivering scripted events and capturing the
+/// output. Copies of this backend share the same state. It is not exclusive,
+/// so many headless terminals may run at the same time.
+#[derive(Debug, Clone)]
+pub struct HeadlessBackend {
+    /// Shared state of the backend.
+    state: Arc<Mutex<State>>,
+    /// Notified whenever something is written to the output.
+    written: Arc<Notify>,
+}
+
+impl HeadlessBackend {
+    /// Creates an in-memory backend with the given screen size and an empty
+    /// script.
+    pub fn new(size: Vec2) -> Self {
+        let state = State { size, script: VecDeque::new(), output: Vec::new() };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            written: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Appends an event to the script. A resize with a new size changes the
+    /// size of the in-memory terminal when delivered.
+    pub fn push_event(&self, event: Event) {
+        self.state.lock().unwrap().script.push_back(event);
+    }
+
+    /// Returns a copy of everything written to the output so far.
+    pub fn output_bytes(&self) -> Vec<u8> {
+        self.state.lock().unwrap().output.clone()
+    }
+
+    /// Waits until everything written to the output so far satisfies the
+    /// given condition, e.g. until a frame showing some text is rendered.
+    /// The condition is checked again whenever something is written.
+    pub async fn wait_output<F>(&self, mut condition: F)
+    where
+        F: FnMut(&[u8]) -> bool,
+    {
+        loop {
+            let written = self.written.notified();
+            if condition(&self.state.lock().unwrap().output) {
+                break;
+            }
+            written.await;
+        }
+    }
+
+    /// Appends the given bytes to the output.
+    fn write(&self, buf: &[u8]) {
+        self.state.lock().unwrap().output.extend_from_slice(buf);
+        self.written.notify_waiters();
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn exclusive(&self) -> bool {
+        false
+    }
+
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Vec2> {
+        Ok(self.state.lock().unwrap().size)
+    }
+
+    fn poll_event(&self) -> io::Result<Option<Event>> {
+        let mut state = self.state.lock().unwrap();
+        let event = state.script.pop_front();
+        if let Some(Event::Resize(ResizeEvent { size: Some(size) })) = event {
+            state.size = size;
+        }
+        Ok(event)
+    }
+
+    fn output(&self) -> Output {
+        Box::new(HeadlessOutput { backend: self.clone() })
+    }
+
+    fn write_blocking(&self, buf: &[u8]) -> io::Result<()> {
+        self.write(buf);
+        Ok(())
+    }
+}
+
+/// Output sink of the headless backend.
+#[derive(Debug)]
+struct HeadlessOutput {
+    /// The backend whose output this is.
+    backend: HeadlessBackend,
+}
+
+impl AsyncWrite for HeadlessOutput {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.backend.write(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,1046 @@
+use crate::{
+    backend::{Backend, Output},
+    color::{BasicColor, Color2, ColorSupport, RgbColor},
+    coord::Vec2,
+    error::Error,
+    event::{
+        Event,
+        EventLog,
+        Key,
+        KeyEvent,
+        MouseButton,
+        MouseEvent,
+        MouseKind,
+        ResizeEvent,
+    },
+    headless::{Headless, HeadlessBackend},
+    screen::{CursorShape, FrameStats, Snapshot},
+    string::TermString,
+    style::Style,
+    terminal::{Builder, NotifyProtocol, Terminal},
+    testing::SharedWriter,
+    tile::Link,
+    ui::{input::InputDialog, menu::Menu},
+};
+use std::{
+    io,
+    process::Command,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::time;
+
+fn key(main_key: Key) -> KeyEvent {
+    KeyEvent { main_key, ctrl: false, alt: false, shift: false }
+}
+
+/// Whether the output moves the cursor to an absolute row.
+fn moves_to_row(output: &str) -> bool {
+    output.split('\u{1b}').any(|seq| {
+        let params = seq.strip_prefix('[').unwrap_or("");
+        let end = params.trim_start_matches(|ch: char| ch.is_ascii_digit());
+        let end = end.strip_prefix(';').unwrap_or("");
+        let end = end.trim_start_matches(|ch: char| ch.is_ascii_digit());
+        params.len() > end.len() && end.starts_with('H')
+    })
+}
+
+/// Number of times the given text appears in the output.
+fn count(output: &[u8], text: &str) -> usize {
+    String::from_utf8_lossy(output).matches(text).count()
+}
+
+async fn wait_esc(term: &mut Terminal) -> Result<Vec<Event>, Error> {
+    let mut events = Vec::new();
+    loop {
+        let mut session = term.listen().await?;
+        match session.event() {
+            Some(Event::Key(KeyEvent { main_key: Key::Esc, .. })) => break,
+            Some(event) => events.push(event),
+            None => (),
+        }
+    }
+    Ok(events)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn menu_selection() {
+    let outcome = Headless::new(Vec2 { x: 40, y: 12 })
+        .events(vec![key(Key::Down), key(Key::Down), key(Key::Enter)])
+        .run(|mut term| async move {
+            let options: Vec<TermString> =
+                vec![tstring!["foo"], tstring!["bar"], tstring!["baz"]];
+            let menu = Menu::new(tstring!["Pick one"], options);
+            menu.select(&mut term).await
+        })
+        .await
+        .unwrap();
+    assert_eq!(outcome.value.unwrap(), 2);
+}
+
+/// Selects an item of a long menu, given some keys.
+async fn select_long(keys: Vec<Key>) -> (usize, Snapshot) {
+    let outcome = Headless::new(Vec2 { x: 40, y: 12 })
+        .events(keys.into_iter().map(key).collect::<Vec<_>>())
+        .run(|mut term| async move {
+            let options: Vec<TermString> = (0 .. 30)
+                .map(|i| TermString::new_lossy(format!("option {}", i)))
+                .collect();
+            let menu = Menu::new(tstring!["Pick one"], options);
+            menu.select(&mut term).await
+        })
+        .await
+        .unwrap();
+    (outcome.value.unwrap(), outcome.screen)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn menu_jumps() {
+    let (selected, screen) = select_long(vec![Key::End, Key::Enter]).await;
+    assert_eq!(selected, 29);
+    let rows: Vec<_> =
+        (0 .. screen.size().y).map(|y| screen.row_text(y)).collect();
+    assert!(rows.iter().any(|row| row.contains("option 29")));
+    assert!(!rows.iter().any(|row| row.contains("option 0 ")));
+
+    let keys = vec![Key::End, Key::Home, Key::Enter];
+    assert_eq!(select_long(keys).await.0, 0);
+
+    let (page, _) = select_long(vec![Key::PageDown, Key::Enter]).await;
+    assert!(page > 0);
+    let keys = vec![Key::PageDown, Key::PageDown, Key::Enter];
+    assert_eq!(select_long(keys).await.0, 2 * page);
+    let keys = vec![Key::PageDown, Key::PageDown, Key::PageUp, Key::Enter];
+    assert_eq!(select_long(keys).await.0, page);
+    let keys = vec![Key::End, Key::PageDown, Key::Enter];
+    assert_eq!(select_long(keys).await.0, 29);
+}
+
+/// Selects an item of a menu with a cancel option, given some events.
+async fn select_with_cancel(events: Vec<Event>) -> (Option<usize>, Snapshot) {
+    let outcome = Headless::new(Vec2 { x: 40, y: 25 })
+        .events(events)
+        .run(|mut term| async move {
+            let options: Vec<TermString> =
+                vec![tstring!["foo"], tstring!["bar"], tstring!["baz"]];
+            let menu = Menu::new(tstring!["Pick one"], options);
+            menu.select_with_cancel(&mut term).await
+        })
+        .await
+        .unwrap();
+    (outcome.value.unwrap(), outcome.screen)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn menu_mouse_selection() {
+    let click = |y| {
+        Event::Mouse(MouseEvent {
+            kind: MouseKind::Press(MouseButton::Left),
+            pos: Vec2 { x: 20, y },
+            ctrl: false,
+            alt: false,
+            shift: false,
+        })
+    };
+
+    let (_, screen) = select_with_cancel(vec![Event::Key(key(Key::Esc))]).await;
+    let row_of = |text| {
+        (0 .. screen.size().y)
+            .find(|&y| screen.row_text(y).contains(text))
+            .unwrap()
+    };
+    let bar = row_of("bar");
+    let cancel = row_of("CANCEL");
+
+    assert_eq!(select_with_cancel(vec![click(bar)]).await.0, Some(1));
+    assert_eq!(select_with_cancel(vec![click(cancel)]).await.0, None);
+    // Clicking outside of the options changes nothing.
+    let events = vec![click(0), Event::Key(key(Key::Enter))];
+    assert_eq!(select_with_cancel(events).await.0, Some(0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn screen_contents() {
+    let outcome = Headless::new(Vec2 { x: 20, y: 4 })
+        .event(key(Key::Esc))
+        .run(|mut term| async move {
+            {
+                let mut session = term.lock_now().await?;
+                let style = Style::default().top_margin(1);
+                session.screen().styled_text(&tstring!["Hello!"], style);
+            }
+            wait_esc(&mut term).await
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+    assert_eq!(outcome.screen.size(), Vec2 { x: 20, y: 4 });
+    assert_eq!(outcome.screen.row_text(0).trim_end(), "");
+    assert_eq!(outcome.screen.row_text(1).trim_end(), "Hello!");
+    assert!(!outcome.output.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scripted_resizes() {
+    let small = Vec2 { x: 10, y: 3 };
+    let large = Vec2 { x: 30, y: 8 };
+    let builder = Builder::new().min_screen(Vec2 { x: 20, y: 4 });
+    let outcome = Headless::new(Vec2 { x: 20, y: 4 })
+        .builder(builder)
+        .events(vec![
+            Event::Resize(ResizeEvent { size: Some(small) }),
+            Event::Resize(ResizeEvent { size: Some(large) }),
+            Event::Key(key(Key::Esc)),
+        ])
+        .run(|mut term| async move { wait_esc(&mut term).await })
+        .await
+        .unwrap();
+    let events = outcome.value.unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::Resize(ResizeEvent { size: None }),
+            Event::Resize(ResizeEvent { size: Some(large) }),
+        ]
+    );
+    assert_eq!(outcome.screen.size(), large);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn asciicast_session() {
+    let writer = SharedWriter::default();
+    let size = Vec2 { x: 20, y: 4 };
+    let builder =
+        Builder::new().min_screen(size).record_asciicast(writer.clone());
+    let large = Vec2 { x: 30, y: 8 };
+    let outcome = Headless::new(size)
+        .builder(builder)
+        .events(vec![
+            Event::Resize(ResizeEvent { size: Some(large) }),
+            Event::Key(key(Key::Esc)),
+        ])
+        .run(|mut term| async move {
+            {
+                let mut session = term.lock_now().await?;
+                session
+                    .screen()
+                    .styled_text(&tstring!["Hello!"], Style::default());
+            }
+            wait_esc(&mut term).await
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+
+    let recording = writer.text();
+    let mut lines = recording.lines();
+    let header = lines.next().unwrap();
+    assert!(header.starts_with("{\"version\": 2, \"width\": 20, \"height\": 4"));
+    let events = lines.collect::<Vec<_>>();
+    assert!(events.iter().all(|event| event.starts_with('[')));
+    assert!(events.iter().any(|event| event.ends_with(", \"r\", \"30x8\"]")));
+    assert!(events.iter().all(
+        |event| event.contains(", \"o\", ") || event.contains(", \"r\", ")
+    ));
+}
+
+/// A writer which always fails, like a closed pipe.
+#[derive(Debug, Clone, Copy, Default)]
+struct FailingWriter;
+
+impl io::Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_asciicast_keeps_running() {
+    let size = Vec2 { x: 20, y: 4 };
+    let builder =
+        Builder::new().min_screen(size).record_asciicast(FailingWriter);
+    let outcome = Headless::new(size)
+        .builder(builder)
+        .events(vec![
+            Event::Resize(ResizeEvent { size: Some(Vec2 { x: 30, y: 8 }) }),
+            Event::Key(key(Key::Esc)),
+        ])
+        .run(|mut term| async move {
+            let events = wait_esc(&mut term).await?;
+            term.lock_now()
+                .await?
+                .screen()
+                .styled_text(&tstring!["Hello!"], Style::default());
+            Ok::<_, Error>(events)
+        })
+        .await
+        .unwrap();
+    assert_eq!(outcome.value.unwrap().len(), 1);
+    assert_eq!(outcome.screen.row_text(0).trim_end(), "Hello!");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replay_event_log() {
+    let size = Vec2 { x: 20, y: 4 };
+    let typing = |mut term: Terminal| async move {
+        let events = wait_esc(&mut term).await?;
+        let mut session = term.lock_now().await?;
+        let text = format!("{:?}", events.len());
+        session
+            .screen()
+            .styled_text(&TermString::new_lossy(text), Style::default());
+        Ok::<_, Error>(events)
+    };
+
+    let writer = SharedWriter::default();
+    let builder = Builder::new().min_screen(size).record_events(writer.clone());
+    let recorded = Headless::new(size)
+        .builder(builder)
+        .events(vec![
+            Event::Key(key(Key::Char('x'))),
+            Event::Resize(ResizeEvent { size: Some(Vec2 { x: 25, y: 5 }) }),
+            Event::Key(key(Key::Up)),
+            Event::Key(key(Key::Esc)),
+        ])
+        .run(typing)
+        .await
+        .unwrap();
+
+    let text = writer.text();
+    let log = EventLog::parse(&text).unwrap();
+    assert_eq!(log.entries().len(), 4);
+    assert_eq!(log.entries().last().unwrap().epoch, 4);
+
+    let builder = Builder::new().min_screen(size).replay_events(log);
+    let replayed =
+        Headless::new(size).builder(builder).run(typing).await.unwrap();
+    assert_eq!(replayed.value.unwrap(), recorded.value.unwrap());
+    assert_eq!(replayed.screen, recorded.screen);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replay_with_frame_clock() {
+    let size = Vec2 { x: 20, y: 4 };
+    let ticking = |mut term: Terminal| async move {
+        let mut seen = Vec::new();
+        loop {
+            let frame = term.next_frame().await?;
+            let mut session = term.lock_now().await?;
+            let event = session.event();
+            if let Some(Event::Key(KeyEvent { main_key, .. })) = event {
+                seen.push((frame, main_key));
+            }
+            let text = format!("{} {}", frame, seen.len());
+            session.screen().clear(BasicColor::Black.into());
+            session
+                .screen()
+                .styled_text(&TermString::new_lossy(text), Style::default());
+            if event == Some(Event::Key(key(Key::Esc))) {
+                break Ok::<_, Error>(seen);
+            }
+        }
+    };
+
+    let text = "\
+        10 1 3 key char:61 ---\n95 2 7 key char:62 ---\n120 3 12 key char:63 \
+                ---\n130 4 15 key esc ---\n";
+    let log = EventLog::parse(text).unwrap();
+    let mut outcomes = Vec::new();
+    for _ in 0 .. 2 {
+        let builder = Builder::new()
+            .min_screen(size)
+            .frame_clock(true)
+            .frame_time(Duration::from_millis(20))
+            .replay_events(log.clone());
+        let outcome =
+            Headless::new(size).builder(builder).run(ticking).await.unwrap();
+        outcomes.push(outcome);
+    }
+
+    let seen = outcomes[0].value.as_ref().unwrap();
+    assert_eq!(
+        seen,
+        &vec![
+            (3, Key::Char('a')),
+            (7, Key::Char('b')),
+            (12, Key::Char('c')),
+            (15, Key::Esc),
+        ]
+    );
+    assert_eq!(outcomes[1].value.as_ref().unwrap(), seen);
+    assert_eq!(outcomes[0].screen.row_text(0).trim_end(), "15 4");
+    assert_eq!(outcomes[1].screen, outcomes[0].screen);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn panic_hook_restores() {
+    let size = Vec2 { x: 20, y: 4 };
+    let headless = Headless::new(size)
+        .builder(Builder::new().min_screen(size).panic_hook(true));
+    let backend = headless.backend().clone();
+    let handle = tokio::spawn(headless.run(|_| async move {
+        panic!("expected panic");
+    }));
+    assert!(handle.await.unwrap_err().is_panic());
+
+    let output = String::from_utf8(backend.output_bytes()).unwrap();
+    let leave = "\u{1b}[?1049l";
+    assert_eq!(output.matches(leave).count(), 1);
+    assert!(output.trim_end().ends_with(leave));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suspend_redraws() {
+    let size = Vec2 { x: 20, y: 4 };
+    let headless = Headless::new(size);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .run(|mut term| async move {
+            term.lock_now()
+                .await?
+                .screen()
+                .styled_text(&tstring!["Hello!"], Style::default());
+            backend.wait_output(|output| count(output, "Hello!") == 1).await;
+            term.suspend().await?;
+            backend.wait_output(|output| count(output, "Hello!") == 2).await;
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    assert_eq!(output.matches("\u{1b}[?1049h").count(), 2);
+    assert_eq!(output.matches("\u{1b}[?1049l").count(), 2);
+    assert_eq!(output.matches("Hello!").count(), 2);
+    assert_eq!(outcome.screen.row_text(0).trim_end(), "Hello!");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suspend_on_ctrl_z() {
+    let size = Vec2 { x: 20, y: 4 };
+    let ctrl_z = KeyEvent {
+        main_key: Key::Char('z'),
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+    let builder = Builder::new().min_screen(size).suspend_on_ctrl_z(true);
+    let outcome = Headless::new(size)
+        .builder(builder)
+        .events(vec![Event::Key(ctrl_z), Event::Key(key(Key::Esc))])
+        .run(|mut term| async move { wait_esc(&mut term).await })
+        .await
+        .unwrap();
+    assert_eq!(outcome.value.unwrap(), Vec::new());
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    assert_eq!(output.matches("\u{1b}[?1049l").count(), 2);
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn run_external_program() {
+    let size = Vec2 { x: 20, y: 4 };
+    let outcome = Headless::new(size)
+        .run(|mut term| async move {
+            let status = term.run_external(Command::new("true")).await?;
+            let missing = Command::new("andiskaz-no-such-program");
+            let failed = term.run_external(missing).await.is_err();
+            Ok::<_, Error>((status.success(), failed))
+        })
+        .await
+        .unwrap();
+    assert_eq!(outcome.value.unwrap(), (true, true));
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    assert_eq!(output.matches("\u{1b}[?1049h").count(), 3);
+    assert_eq!(output.matches("\u{1b}[?1049l").count(), 3);
+    assert!(output.trim_end().ends_with("\u{1b}[?1049l"));
+}
+
+/// A headless backend taken as the process' own terminal. Suspending it
+/// continues the process at once, as the shell's `fg` would.
+#[cfg(unix)]
+#[derive(Debug)]
+struct OwnTerminal(HeadlessBackend);
+
+/// Serializes the tests running an [`OwnTerminal`], since only one may run at
+/// a time, and signals are received by the whole process.
+#[cfg(unix)]
+static OWN_TERMINAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[cfg(unix)]
+impl Backend for OwnTerminal {
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        self.0.enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        self.0.disable_raw_mode()
+    }
+
+    fn size(&self) -> io::Result<Vec2> {
+        self.0.size()
+    }
+
+    fn poll_event(&self) -> io::Result<Option<Event>> {
+        self.0.poll_event()
+    }
+
+    fn output(&self) -> Output {
+        self.0.output()
+    }
+
+    fn suspend(&self) -> io::Result<()> {
+        if unsafe { libc::raise(libc::SIGCONT) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    fn write_blocking(&self, buf: &[u8]) -> io::Result<()> {
+        self.0.write_blocking(buf)
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn continued_sets_up_again() {
+    let _own = OWN_TERMINAL.lock().await;
+    let size = Vec2 { x: 20, y: 4 };
+    let backend = HeadlessBackend::new(size);
+    let builder =
+        Builder::new().min_screen(size).backend(OwnTerminal(backend.clone()));
+    let result = builder
+        .run(|mut term| async move {
+            term.lock_now()
+                .await?
+                .screen()
+                .styled_text(&tstring!["Hello!"], Style::default());
+            unsafe {
+                libc::raise(libc::SIGCONT);
+            }
+            let redrawn = backend.wait_output(|output| {
+                let output = String::from_utf8_lossy(output);
+                let mut setups = output.rsplit("\u{1b}[?1049h");
+                let last = setups.next().unwrap();
+                setups.count() >= 2 && last.contains("Hello!")
+            });
+            Ok::<_, Error>(time::timeout(Duration::from_secs(5), redrawn).await)
+        })
+        .await
+        .unwrap();
+    assert!(result.unwrap().is_ok());
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn suspend_sets_up_once() {
+    let _own = OWN_TERMINAL.lock().await;
+    let size = Vec2 { x: 20, y: 4 };
+    let backend = HeadlessBackend::new(size);
+    let builder =
+        Builder::new().min_screen(size).backend(OwnTerminal(backend.clone()));
+    let written = backend.clone();
+    builder
+        .run(|mut term| async move {
+            term.set_title("Title").await?;
+            term.lock_now()
+                .await?
+                .screen()
+                .styled_text(&tstring!["Hello!"], Style::default());
+            backend.wait_output(|output| count(output, "Hello!") == 1).await;
+            term.suspend().await?;
+            backend.wait_output(|output| count(output, "Hello!") == 2).await;
+            // Gives the continuation time to be (wrongly) handled again.
+            time::sleep(Duration::from_millis(100)).await;
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let output = String::from_utf8(written.output_bytes()).unwrap();
+    assert_eq!(output.matches("\u{1b}[?1049h").count(), 2);
+    assert_eq!(output.matches("\u{1b}[?1049l").count(), 2);
+    assert_eq!(output.matches("\u{1b}[22;0t").count(), 2);
+    assert_eq!(output.matches("\u{1b}[23;0t").count(), 2);
+    assert_eq!(output.matches("Hello!").count(), 2);
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_continued_keeps_region() {
+    let _own = OWN_TERMINAL.lock().await;
+    let size = Vec2 { x: 20, y: 4 };
+    let backend = HeadlessBackend::new(size);
+    let builder = Builder::new()
+        .min_screen(size)
+        .inline(size.y)
+        .backend(OwnTerminal(backend.clone()));
+    let written = backend.clone();
+    builder
+        .run(|mut term| async move {
+            term.lock_now().await?.screen().styled_text(
+                &tstring!["Hello!"],
+                Style::default().top_margin(2),
+            );
+            backend.wait_output(|output| count(output, "Hello!") == 1).await;
+            unsafe {
+                libc::raise(libc::SIGCONT);
+            }
+            backend.wait_output(|output| count(output, "Hello!") == 2).await;
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The cursor is moved from the end of the text back to the top of the
+    // screen before rows are reserved again.
+    let output = String::from_utf8(written.output_bytes()).unwrap();
+    let hello = output.find("Hello!").unwrap();
+    let setup = "\r\n\n\n\u{1b}[3A";
+    assert!(output[hello ..].contains(&format!("\u{1b}[2A\u{1b}[1G{}", setup)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_menu() {
+    let size = Vec2 { x: 40, y: 12 };
+    let builder = Builder::new().min_screen(size).inline(size.y);
+    let outcome = Headless::new(Vec2 { x: 40, y: 30 })
+        .builder(builder)
+        .events(vec![key(Key::Down), key(Key::Down), key(Key::Enter)])
+        .run(|mut term| async move {
+            let options: Vec<TermString> =
+                vec![tstring!["foo"], tstring!["bar"], tstring!["baz"]];
+            let menu = Menu::new(tstring!["Pick one"], options);
+            menu.select(&mut term).await
+        })
+        .await
+        .unwrap();
+    assert_eq!(outcome.value.unwrap(), 2);
+    assert_eq!(outcome.screen.size(), size);
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    assert!(!output.contains("\u{1b}[?1049h"));
+    assert!(!moves_to_row(&output));
+    assert!(output.contains("Pick"));
+    assert!(output.ends_with("\r\n"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_input_dialog() {
+    let size = Vec2 { x: 40, y: 12 };
+    let builder = Builder::new().min_screen(size).inline(size.y);
+    let outcome = Headless::new(Vec2 { x: 40, y: 30 })
+        .builder(builder)
+        .events(vec![key(Key::Char('h')), key(Key::Char('i')), key(Key::Enter)])
+        .run(|mut term| async move {
+            let mut dialog = InputDialog::new(
+                tstring!["Name?"],
+                TermString::default(),
+                10,
+                |ch| ch.is_alphabetic(),
+            );
+            dialog.select(&mut term).await
+        })
+        .await
+        .unwrap();
+    assert_eq!(outcome.value.unwrap(), tstring!["hi"]);
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    assert!(!output.contains("\u{1b}[?1049h"));
+    assert!(!moves_to_row(&output));
+    assert!(output.ends_with("\r\n"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn input_dialog_cursor() {
+    // An odd width, so that centering the box rounds.
+    let size = Vec2 { x: 21, y: 8 };
+    let builder = Builder::new().min_screen(size);
+    let headless = Headless::new(size).builder(builder);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .run(move |mut term| async move {
+            let mut watcher = term.clone();
+            let dialog = tokio::spawn(async move {
+                let mut dialog = InputDialog::new(
+                    tstring!["Name?"],
+                    tstring!["abcd"],
+                    6,
+                    |ch| ch.is_alphabetic(),
+                );
+                dialog.select(&mut term).await
+            });
+
+            // Every key changes the box or the cursor.
+            let mut states = Vec::new();
+            let mut prev = None;
+            let keys = [
+                None,
+                Some(Key::End),
+                Some(Key::Home),
+                Some(Key::Delete),
+                Some(Key::End),
+            ];
+            for main_key in keys.iter().copied() {
+                if let Some(main_key) = main_key {
+                    backend.push_event(Event::Key(key(main_key)));
+                }
+                let changed = async {
+                    loop {
+                        let mut session = watcher.lock_now().await?;
+                        let screen = session.screen();
+                        if let Some(cursor) = screen.cursor() {
+                            let row = screen.snapshot().row_text(cursor.y);
+                            let state = (row.trim_end().to_owned(), cursor);
+                            if prev.as_ref() != Some(&state) {
+                                break Ok::<_, Error>(state);
+                            }
+                        }
+                        drop(session);
+                        time::sleep(Duration::from_millis(1)).await;
+                    }
+                };
+                let state = time::timeout(Duration::from_secs(5), changed)
+                    .await
+                    .unwrap()?;
+                states.push(state.clone());
+                prev = Some(state);
+            }
+
+            backend.push_event(Event::Key(key(Key::Enter)));
+            let selected = dialog.await.unwrap()?;
+            Ok::<_, Error>((states, selected))
+        })
+        .await
+        .unwrap();
+    let (states, selected) = outcome.value.unwrap();
+    assert_eq!(selected, tstring!["bcd"]);
+
+    // The 6 columns wide box starts at (21 - 6) / 2.
+    let y = states[0].1.y;
+    assert_eq!(
+        states,
+        vec![
+            ("       abcd".to_owned(), Vec2 { x: 7, y }),
+            ("       abcd".to_owned(), Vec2 { x: 11, y }),
+            ("       abcd".to_owned(), Vec2 { x: 7, y }),
+            ("       bcd".to_owned(), Vec2 { x: 7, y }),
+            ("       bcd".to_owned(), Vec2 { x: 10, y }),
+        ]
+    );
+    assert_eq!(outcome.screen.cursor(), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cursor_control() {
+    let size = Vec2 { x: 20, y: 4 };
+    let point = Vec2 { x: 3, y: 1 };
+    let headless = Headless::new(size);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .run(move |mut term| async move {
+            {
+                let mut session = term.lock_now().await?;
+                session.screen().set_cursor(Some(point));
+                session.screen().set_cursor_shape(CursorShape::Bar, false);
+            }
+            backend
+                .wait_output(|output| count(output, "\u{1b}[?25h") > 0)
+                .await;
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+    assert_eq!(outcome.screen.cursor(), Some(point));
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    let shown = ["\u{1b}[6 q", "\u{1b}[2;4H", "\u{1b}[?25h"].concat();
+    assert!(output.contains(&shown));
+    assert!(output.contains("\u{1b}[0 q"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn title_bell_notifications() {
+    let size = Vec2 { x: 20, y: 4 };
+    let outcome = Headless::new(size)
+        .run(|term| async move {
+            term.set_title("Demo\u{7}!").await?;
+            term.set_title("Demo 2").await?;
+            term.bell().await?;
+            term.notify(NotifyProtocol::Osc777, "Build; ok", "done").await?;
+            term.notify(NotifyProtocol::Osc9, "Build", "done").await?;
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    let push = "\u{1b}[22;0t";
+    let pop = "\u{1b}[23;0t";
+    assert_eq!(output.matches(push).count(), 1);
+    assert!(output.contains("\u{1b}]0;Demo!\u{7}"));
+    assert!(output.contains("\u{1b}]0;Demo 2\u{7}\u{7}"));
+    assert!(output.contains("\u{1b}]777;notify;Build ok;done\u{1b}\\"));
+    assert!(output.contains("\u{1b}]9;Build: done\u{1b}\\"));
+    assert_eq!(output.matches(pop).count(), 1);
+    assert!(output.find(pop) > output.find("Build: done"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn title_bell_while_too_small() {
+    let builder = Builder::new().min_screen(Vec2 { x: 20, y: 4 });
+    let outcome = Headless::new(Vec2 { x: 10, y: 3 })
+        .builder(builder)
+        .run(|term| async move {
+            let written = async {
+                term.set_title("Demo").await?;
+                term.bell().await
+            };
+            time::timeout(Duration::from_secs(5), written).await
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap().unwrap();
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    let title = output.find("\u{1b}[22;0t\u{1b}]0;Demo\u{7}\u{7}");
+    assert!(title.is_some());
+    assert!(output.find("\u{1b}[23;0t") > title);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_dropped_frames_while_too_small() {
+    let size = Vec2 { x: 20, y: 4 };
+    let builder = Builder::new().min_screen(size);
+    let headless = Headless::new(size).builder(builder);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .events(vec![Event::Resize(ResizeEvent {
+            size: Some(Vec2 { x: 10, y: 3 }),
+        })])
+        .run(|mut term| async move {
+            backend.wait_output(|output| count(output, "RESIZE") > 0).await;
+            for _ in 0 .. 5 {
+                let mut session = term.lock_now().await?;
+                session
+                    .screen()
+                    .styled_text(&tstring!["Hello!"], Style::default());
+                drop(session);
+                time::sleep(Duration::from_millis(20)).await;
+            }
+            Ok::<_, Error>(term.stats())
+        })
+        .await
+        .unwrap();
+    let stats = outcome.value.unwrap();
+    assert_eq!(stats.dropped_frames, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hyperlinks() {
+    let size = Vec2 { x: 20, y: 4 };
+    let headless = Headless::new(size);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .run(|mut term| async move {
+            {
+                let mut session = term.lock_now().await?;
+                let link = Link::new("https://example.com/\u{7}docs");
+                let style = Style::default().link(link);
+                session.screen().styled_text(&tstring!["Docs"], style);
+            }
+            backend.wait_output(|output| count(output, "Docs") > 0).await;
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+    assert_eq!(
+        outcome.screen.get(Vec2 { x: 0, y: 0 }).unwrap().link,
+        Some(Link::new("https://example.com/docs"))
+    );
+
+    let output = String::from_utf8(outcome.output).unwrap();
+    let link = [
+        "\u{1b}]8;;https://example.com/docs\u{1b}\\",
+        "Docs",
+        "\u{1b}]8;;\u{1b}\\",
+    ]
+    .concat();
+    assert!(output.contains(&link));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn color_downsampling() {
+    let size = Vec2 { x: 20, y: 4 };
+    let render = |support| async move {
+        let builder = Builder::new().min_screen(size).color_support(support);
+        let headless = Headless::new(size).builder(builder);
+        let backend = headless.backend().clone();
+        let outcome = headless
+            .run(move |mut term| async move {
+                {
+                    let mut session = term.lock_now().await?;
+                    assert_eq!(session.screen().color_support(), support);
+                    let color = RgbColor { red: 250, green: 10, blue: 5 };
+                    let colors = Color2 {
+                        foreground: color.into(),
+                        background: BasicColor::Black.into(),
+                    };
+                    let style = Style::with_colors(colors);
+                    session.screen().styled_text(&tstring!["Hot"], style);
+                }
+                backend.wait_output(|output| count(output, "Hot") > 0).await;
+                Ok::<_, Error>(())
+            })
+            .await
+            .unwrap();
+        outcome.value.unwrap();
+        String::from_utf8(outcome.output).unwrap()
+    };
+
+    let output = render(ColorSupport::Rgb).await;
+    assert!(output.contains("\u{1b}[38;2;250;10;5mHot"));
+    let output = render(ColorSupport::EightBit).await;
+    assert!(output.contains("\u{1b}[38;5;196mHot"));
+    let output = render(ColorSupport::Basic).await;
+    assert!(output.contains("\u{1b}[38;5;9mHot"));
+    let output = render(ColorSupport::NoColor).await;
+    assert!(output.contains("\u{1b}[39mHot"));
+    assert!(!output.contains("\u{1b}[38;"));
+    assert!(!output.contains("\u{1b}[48;"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn synchronized_output() {
+    let size = Vec2 { x: 20, y: 4 };
+    let render = |synchronized: Option<bool>| async move {
+        let mut builder = Builder::new().min_screen(size);
+        if let Some(synchronized) = synchronized {
+            builder = builder.synchronized_output(synchronized);
+        }
+        let headless = Headless::new(size).builder(builder);
+        let backend = headless.backend().clone();
+        let outcome = headless
+            .run(|mut term| async move {
+                term.lock_now()
+                    .await?
+                    .screen()
+                    .styled_text(&tstring!["Hello!"], Style::default());
+                backend.wait_output(|output| count(output, "Hello!") > 0).await;
+                Ok::<_, Error>(())
+            })
+            .await
+            .unwrap();
+        outcome.value.unwrap();
+        String::from_utf8(outcome.output).unwrap()
+    };
+
+    let begin = "\u{1b}[?2026h";
+    let end = "\u{1b}[?2026l";
+    // Enabled by default.
+    let output = render(None).await;
+    assert!(output.matches(begin).count() > 0);
+    assert_eq!(output.matches(begin).count(), output.matches(end).count());
+    let hello = output.find("Hello!").unwrap();
+    assert!(output[.. hello].rfind(begin) > output[.. hello].rfind(end));
+    assert!(output[hello ..].contains(end));
+
+    let output = render(Some(false)).await;
+    assert!(!output.contains(begin));
+    assert!(!output.contains(end));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn renders_on_demand() {
+    let size = Vec2 { x: 20, y: 4 };
+    let builder = Builder::new()
+        .min_screen(size)
+        .frame_time(Duration::from_millis(1))
+        .synchronized_output(true);
+    let headless = Headless::new(size).builder(builder);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .run(|mut term| async move {
+            for text in &[tstring!["Hello!"], tstring!["Bye!"]] {
+                term.lock_now()
+                    .await?
+                    .screen()
+                    .styled_text(text, Style::default().top_margin(1));
+                let text = text.as_str();
+                backend.wait_output(|output| count(output, text) > 0).await;
+                time::sleep(Duration::from_millis(100)).await;
+            }
+            Ok::<_, Error>(())
+        })
+        .await
+        .unwrap();
+    outcome.value.unwrap();
+    assert_eq!(outcome.screen.row_text(1).trim_end(), "Bye!o!");
+
+    // At least one frame for each change, but far fewer than the hundreds
+    // rendered every frame time while the screen is unchanged.
+    let output = String::from_utf8(outcome.output).unwrap();
+    let frames = output.matches("\u{1b}[?2026h").count();
+    assert!(frames >= 2, "{} frames", frames);
+    assert!(frames < 20, "{} frames", frames);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn frame_stats() {
+    let size = Vec2 { x: 20, y: 4 };
+    let frames = Arc::new(Mutex::new(Vec::<FrameStats>::new()));
+    let hook_frames = frames.clone();
+    let builder = Builder::new()
+        .min_screen(size)
+        .on_frame(move |stats| hook_frames.lock().unwrap().push(*stats));
+    let headless = Headless::new(size).builder(builder);
+    let backend = headless.backend().clone();
+    let outcome = headless
+        .run(|mut term| async move {
+            let start = Instant::now();
+            {
+                let mut session = term.lock_now().await?;
+                session
+                    .screen()
+                    .styled_text(&tstring!["Hello!"], Style::default());
+                time::sleep(Duration::from_millis(30)).await;
+            }
+            let held = start.elapsed();
+            backend.wait_output(|output| count(output, "Hello!") > 0).await;
+            // Frames are rendered, and their statistics collected, while the
+            // screen is locked.
+            drop(term.lock_now().await?);
+            Ok::<_, Error>((term.stats(), held))
+        })
+        .await
+        .unwrap();
+    let (stats, held) = outcome.value.unwrap();
+
+    let frames = frames.lock().unwrap();
+    assert_eq!(stats.frames, frames.len() as u64);
+    assert_eq!(stats.last_frame, frames.last().copied());
+    assert_eq!(stats.dropped_frames, 0);
+    let frame = frames.iter().find(|frame| frame.changed_tiles > 0).unwrap();
+    assert_eq!(frame.changed_tiles, 6);
+    assert!(frame.bytes >= "Hello!".len());
+    assert!(frame.app_lock_time >= Duration::from_millis(30));
+    assert!(frame.app_lock_time <= held);
+    assert_eq!(stats.changed_tiles, 6);
+    assert_eq!(
+        stats.bytes,
+        frames.iter().map(|frame| frame.bytes as u64).sum()
+    );
+}
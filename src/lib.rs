@@ -10,27 +10,20 @@
 //! ```no_run
 //! use andiskaz::{
 //!     color::Color2,
-//!     emergency_restore,
 //!     error::Error,
 //!     event::Event,
 //!     style::Style,
-//!     terminal::Terminal,
+//!     terminal::{Builder, Terminal},
 //!     tstring,
 //! };
-//! use std::{panic, process::exit};
+//! use std::process::exit;
 //!
 //! /// Asynchronous main of a tokio project.
 //! #[tokio::main]
 //! async fn main() {
-//!     // Sets panic hook so we can see the panic even if terminal was being used
-//!     // in raw mode.
-//!     panic::set_hook(Box::new(|info| {
-//!         let _ = emergency_restore();
-//!         eprintln!("{}", info);
-//!     }));
-//!
-//! // Creates a terminal with default settings and runs it.
-//!     let result = Terminal::run(term_main).await;
+//!     // Creates a terminal with default settings, plus a panic hook so we can
+//!     // see the panic even if terminal was being used in raw mode, and runs it.
+//!     let result = Builder::new().panic_hook(true).run(term_main).await;
 //!     // If error, prints it out and exits with bad code.
 //!     if let Ok(Err(error)) | Err(error) = result {
 //!         eprintln!("{}", error);
@@ -45,7 +38,7 @@
 //!     // Style for the string.
 //!     let style = Style::with_colors(Color2::default());
 //!     // Initial rendering.
-//!     term.lock_now().await?.screen().styled_text(&string, style);
+//!     term.lock_now().await?.screen().styled_text(&string, style.clone());
 //!
 //!     loop {
 //!         // Awaits for an event (key pressed, screen resized, etc).
@@ -58,10 +51,11 @@
 //!             // User resized screen? Then the whole screen was thrown out,
 //!             // re-rendering is required.
 //!             Some(Event::Resize(_)) => {
-//!                 session.screen().styled_text(&string, style);
+//!                 session.screen().styled_text(&string, style.clone());
 //!             },
-//!             // Won't really happen since we waited for an event.
-//!             None => (),
+//!             // Other events are not relevant here. `None` won't really happen
+//!             // since we waited for an event.
+//!             _ => (),
 //!         }
 //!     }
 //!
@@ -75,8 +69,12 @@
 #[macro_use]
 mod macros;
 mod stdio;
+mod recording;
+#[cfg(test)]
+mod testing;
 
 pub mod error;
+pub mod backend;
 pub mod string;
 pub mod coord;
 pub mod color;
@@ -85,6 +83,7 @@ pub mod style;
 pub mod screen;
 pub mod event;
 pub mod terminal;
+pub mod headless;
 pub mod ui;
 
 #[cfg(feature = "clipboard")]
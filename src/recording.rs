@@ -0,0 +1,119 @@
+//! This module defines where recordings (asciicast sessions and event logs)
+//! are written to. Writing happens in a background thread, so that slow
+//! writers never block the terminal's tasks, and a failing writer only stops
+//! its recording, never the terminal.
+
+use std::{
+    fmt,
+    io::{self, Write},
+    mem,
+    sync::{mpsc, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+/// The writer of a recording, owned by a background thread while recording.
+pub(crate) struct Sink {
+    /// What is recorded, used in error messages.
+    name: &'static str,
+    /// State of the sink.
+    state: Mutex<State>,
+}
+
+/// State of a sink.
+enum State {
+    /// Not recording; holds the writer until the next recording.
+    Idle(Box<dyn Write + Send>),
+    /// Recording through a background thread, which gives the writer back
+    /// when finished.
+    Running {
+        /// Sends data to be written to the background thread.
+        sender: mpsc::Sender<Vec<u8>>,
+        /// The background thread.
+        thread: thread::JoinHandle<(Box<dyn Write + Send>, io::Result<()>)>,
+    },
+    /// The writer was lost in a panic while writing.
+    Lost,
+}
+
+impl Sink {
+    /// Creates a sink of the given recording, which writes to the given
+    /// writer. Nothing is written until [`Sink::start`] is called.
+    pub fn new<W>(name: &'static str, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self { name, state: Mutex::new(State::Idle(Box::new(writer))) }
+    }
+
+    /// Starts a recording, spawning the background thread. Does nothing if
+    /// already started.
+    pub fn start(&self) {
+        let mut state = self.state();
+        if let State::Idle(_) = &*state {
+            let writer = match mem::replace(&mut *state, State::Lost) {
+                State::Idle(writer) => writer,
+                _ => unreachable!(),
+            };
+            let (sender, receiver) = mpsc::channel();
+            let thread = thread::spawn(move || write_all(writer, receiver));
+            *state = State::Running { sender, thread };
+        }
+    }
+
+    /// Queues the given data to be written, without waiting for it. Ignored if
+    /// not recording, or if writing already failed.
+    pub fn write(&self, data: Vec<u8>) {
+        if let State::Running { sender, .. } = &*self.state() {
+            // Fails only if the thread gave up on an error, reported when
+            // finished.
+            let _ = sender.send(data);
+        }
+    }
+
+    /// Finishes a recording, waiting for everything queued to be written and
+    /// flushed. Errors are reported in the standard error, since the terminal
+    /// should have been restored by now. Does nothing if not recording.
+    pub fn finish(&self) {
+        let mut state = self.state();
+        let (sender, thread) = match mem::replace(&mut *state, State::Lost) {
+            State::Running { sender, thread } => (sender, thread),
+            prev => {
+                *state = prev;
+                return;
+            },
+        };
+        drop(sender);
+        if let Ok((writer, result)) = thread.join() {
+            if let Err(error) = result {
+                eprintln!("andiskaz: {} stopped: {}", self.name, error);
+            }
+            *state = State::Idle(writer);
+        }
+    }
+
+    /// Locks the state. The state is still usable after a panic while it was
+    /// locked.
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for Sink {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("Sink").field("name", &self.name).finish()
+    }
+}
+
+/// Body of the background thread: writes everything received, and flushes
+/// the writer once the sender is dropped. Gives up on the first error, so
+/// that further data is discarded.
+fn write_all(
+    mut writer: Box<dyn Write + Send>,
+    receiver: mpsc::Receiver<Vec<u8>>,
+) -> (Box<dyn Write + Send>, io::Result<()>) {
+    let result = receiver
+        .iter()
+        .try_for_each(|data| writer.write_all(&data))
+        .and_then(|()| writer.flush());
+    (writer, result)
+}
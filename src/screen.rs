@@ -1,55 +1,182 @@
 //! This module defines screen related utilities.
 
+#[cfg(test)]
+mod test;
+
 mod buffer;
+mod snapshot;
+mod asciicast;
+mod clock;
+mod cursor;
+mod damage;
+mod render;
+mod stats;
+
+#[cfg(feature = "bench-internals")]
+#[doc(hidden)]
+pub mod bench;
+
+pub(crate) use self::{
+    asciicast::Recorder,
+    clock::FrameClock,
+    stats::{FrameHook, StatsCollector},
+};
+pub use self::{
+    cursor::CursorShape,
+    snapshot::Snapshot,
+    stats::{FrameStats, Stats},
+};
 
 use crate::{
-    color::{self, Color, Color2},
+    backend::Backend,
+    color::{self, BasicColor, Color, Color2, ColorSupport},
     coord,
     coord::{Coord, Vec2},
     error::Error,
-    screen::buffer::ScreenBuffer,
-    stdio,
-    stdio::{restore_screen, save_screen, LockedStdout, Stdout},
+    screen::{buffer::ScreenBuffer, cursor::CursorState, render::Frame},
+    stdio::{self, restore_screen, save_screen, LockedStdout, Stdout},
     string::{TermGrapheme, TermString},
     style::Style,
-    terminal::Shared,
-    tile::{self, Tile},
+    terminal::{NotifyProtocol, Shared},
+    tile::{self, Attributes, Tile},
 };
+use crossterm::Command;
 use std::{
-    fmt::Write,
-    sync::atomic::{AtomicBool, Ordering::*},
-    time::Duration,
+    fmt::{self, Write},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering::*},
+        Arc,
+        Mutex as SyncMutex,
+        MutexGuard as SyncMutexGuard,
+        PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use tokio::{
     io,
-    sync::{Mutex, MutexGuard, Notify},
+    sync::{futures::Notified, Mutex, MutexGuard, Notify},
     task,
-    time,
+    time::{self, MissedTickBehavior},
 };
 
+/// Optional terminal input modes, enabled when the terminal is set up and
+/// disabled when it is cleaned up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct InputModes {
+    /// Whether mouse events are captured.
+    pub mouse_capture: bool,
+    /// Whether bracketed paste is enabled.
+    pub bracketed_paste: bool,
+    /// Whether focus changes are reported.
+    pub focus_reporting: bool,
+}
+
+impl InputModes {
+    /// Writes the sequences enabling these modes into the buffer.
+    fn enable(&self, buf: &mut String) -> fmt::Result {
+        if self.mouse_capture {
+            crossterm::event::EnableMouseCapture.write_ansi(buf)?;
+        }
+        if self.bracketed_paste {
+            crossterm::event::EnableBracketedPaste.write_ansi(buf)?;
+        }
+        if self.focus_reporting {
+            crossterm::event::EnableFocusChange.write_ansi(buf)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the sequences disabling these modes into the buffer.
+    fn disable(&self, buf: &mut String) -> fmt::Result {
+        if self.mouse_capture {
+            crossterm::event::DisableMouseCapture.write_ansi(buf)?;
+        }
+        if self.bracketed_paste {
+            crossterm::event::DisableBracketedPaste.write_ansi(buf)?;
+        }
+        if self.focus_reporting {
+            crossterm::event::DisableFocusChange.write_ansi(buf)?;
+        }
+        Ok(())
+    }
+}
+
 /// Shared memory between terminal handle copies.
 #[derive(Debug)]
 pub(crate) struct ScreenData {
     /// Minimum screen size.
     min_size: Vec2,
+    /// Input modes enabled during setup.
+    modes: InputModes,
+    /// Number of rows reserved below the cursor, if rendering inline instead
+    /// of in the alternate screen.
+    inline: Option<Coord>,
+    /// Colors supported by the terminal.
+    color_support: ColorSupport,
+    /// Whether frames are written as synchronized updates.
+    synchronized: bool,
+    /// Whether trailing blank tiles are erased with `EL`.
+    erase_lines: bool,
+    /// Whether repeated graphemes are written with `REP`.
+    repeat_graphemes: bool,
     /// Frame interval time.
     frame_time: Duration,
+    /// Frame interval time while the terminal is not focused.
+    unfocused_frame_time: Duration,
+    /// The fixed frame clock, if time is counted in frames.
+    clock: Option<FrameClock>,
+    /// Whether the terminal is currently focused.
+    focused: AtomicBool,
     /// Whether the terminal handle has been cleaned up (using
     /// terminal.cleanup).
     cleanedup: AtomicBool,
+    /// Whether the process was stopped by [`Screen::suspend`], and the
+    /// terminal is already set up again once it is continued.
+    stopped_itself: AtomicBool,
+    /// The backend of the terminal.
+    backend: Arc<dyn Backend>,
     /// A lock to the standard output.
     stdout: Stdout,
+    /// Where the output is recorded as asciicast, if anywhere.
+    recorder: Option<Arc<Recorder>>,
+    /// Statistics of the frames.
+    stats: StatsCollector,
+    /// Window title set by the application, if any.
+    title: SyncMutex<Option<String>>,
+    /// Sequences waiting to be written with the next frame, since the stdout
+    /// was locked when they were issued.
+    queued: SyncMutex<String>,
     /// Buffer responsible for rendering the screen.
     buffer: Mutex<ScreenBuffer>,
     /// Notification handle of the screen.
     notifier: Notify,
+    /// Notification handle of frame requests to the renderer.
+    frame_notifier: Notify,
 }
 
 impl ScreenData {
     /// Creates screen data from the given settings. If given actual size is
     /// less than given minimum allowed size, the actual size is replaced by the
     /// minimum size.
-    pub fn new(size: Vec2, min_size: Vec2, frame_time: Duration) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        size: Vec2,
+        min_size: Vec2,
+        frame_time: Duration,
+        unfocused_frame_time: Option<Duration>,
+        frame_clock: bool,
+        modes: InputModes,
+        inline: Option<Coord>,
+        color_support: ColorSupport,
+        synchronized: bool,
+        erase_lines: bool,
+        repeat_graphemes: bool,
+        backend: Arc<dyn Backend>,
+        recorder: Option<Arc<Recorder>>,
+        frame_hook: Option<Arc<FrameHook>>,
+    ) -> Self {
         let corrected_size = if size.x >= min_size.x && size.y >= min_size.y {
             size
         } else {
@@ -57,22 +184,81 @@ impl ScreenData {
         };
         Self {
             min_size,
+            modes,
+            inline,
+            color_support,
+            synchronized,
+            erase_lines,
+            repeat_graphemes,
             frame_time,
+            unfocused_frame_time: unfocused_frame_time.unwrap_or(frame_time),
+            clock: if frame_clock { Some(FrameClock::new()) } else { None },
+            focused: AtomicBool::new(true),
             cleanedup: AtomicBool::new(false),
-            stdout: Stdout::new(),
+            stopped_itself: AtomicBool::new(false),
+            stdout: Stdout::new(backend.output()),
+            backend,
+            recorder,
+            stats: StatsCollector::new(frame_hook),
+            title: SyncMutex::new(None),
+            queued: SyncMutex::new(String::new()),
             buffer: Mutex::new(ScreenBuffer::blank(corrected_size)),
             notifier: Notify::new(),
+            frame_notifier: Notify::new(),
         }
     }
 
+    /// The size of the screen given the actual size of the terminal: only the
+    /// reserved rows when rendering inline, the whole terminal otherwise.
+    pub fn viewport(&self, term_size: Vec2) -> Vec2 {
+        viewport(self.inline, term_size)
+    }
+
+    /// Statistics of the frames.
+    pub fn stats(&self) -> &StatsCollector {
+        &self.stats
+    }
+
+    /// Records whether the terminal is focused.
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.store(focused, Release);
+    }
+
+    /// Frame interval time for the current focus state.
+    pub fn curr_frame_time(&self) -> Duration {
+        if self.focused.load(Acquire) {
+            self.frame_time
+        } else {
+            self.unfocused_frame_time
+        }
+    }
+
+    /// The fixed frame clock, if time is counted in frames.
+    pub fn clock(&self) -> Option<&FrameClock> {
+        self.clock.as_ref()
+    }
+
     /// Notifies all parties subscribed to the screen updates.
     pub fn notify(&self) {
         self.notifier.notify_waiters()
     }
 
-    /// Subscribes to changes in the screen data such as disconnection.
-    async fn subscribe(&self) {
-        self.notifier.notified().await
+    /// Subscribes to changes in the screen data such as disconnection. The
+    /// subscription starts when this method is called, not when the returned
+    /// future is first polled.
+    pub fn subscribe(&self) -> Notified<'_> {
+        self.notifier.notified()
+    }
+
+    /// Asks the renderer for a frame. Requests made before the renderer waits
+    /// for one are not lost.
+    fn request_frame(&self) {
+        self.frame_notifier.notify_one()
+    }
+
+    /// Waits until a frame is requested.
+    async fn frame_requested(&self) {
+        self.frame_notifier.notified().await
     }
 
     /// Locks the screen data into an actual screen handle.
@@ -80,48 +266,318 @@ impl ScreenData {
         Screen::new(self).await
     }
 
-    /// Initialization of the terminal, such as cleaning the screen.
-    pub async fn setup(&self) -> Result<(), Error> {
+    /// Tees a buffer written to the terminal into the recording, if any.
+    fn record_output(&self, buf: &str) {
+        if let Some(recorder) = &self.recorder {
+            recorder.output(buf);
+        }
+    }
+
+    /// Whether the process being continued after stopped (`SIGCONT`) should
+    /// be listened to, so that the terminal is set up again. Only the process'
+    /// own terminal is affected by stopping it.
+    pub fn listens_continued(&self) -> bool {
+        cfg!(unix) && self.backend.exclusive()
+    }
+
+    /// Whether the process was continued after stopped by [`Screen::suspend`],
+    /// which already set the terminal up again. Only the first call after the
+    /// process is continued returns `true`.
+    pub fn take_stopped_itself(&self) -> bool {
+        self.stopped_itself.swap(false, AcqRel)
+    }
+
+    /// Records a resize of the terminal, if recording.
+    fn record_resize(&self, size: Vec2) {
+        if let Some(recorder) = &self.recorder {
+            recorder.resize(size);
+        }
+    }
+
+    /// Window title set by the application, if any. The title is still usable
+    /// after a panic while it was locked.
+    fn title(&self) -> SyncMutexGuard<'_, Option<String>> {
+        self.title.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes the sequences waiting to be written.
+    fn take_queued(&self) -> String {
+        let mut queued =
+            self.queued.lock().unwrap_or_else(PoisonError::into_inner);
+        mem::take(&mut *queued)
+    }
+
+    /// Writes the given sequences to the terminal, through the same lock as
+    /// the frames, so that they are never written in the middle of a frame.
+    /// If the stdout is locked (e.g. while the screen is too small), they are
+    /// queued instead, and written with the next frame, without waiting.
+    async fn write_sequences(&self, buf: &str) -> Result<(), Error> {
+        match self.stdout.try_lock() {
+            Some(mut stdout) => {
+                let mut queued = self.take_queued();
+                queued.push_str(buf);
+                stdout.write_and_flush(queued.as_bytes()).await?;
+                self.record_output(&queued);
+            },
+            None => {
+                self.queued
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push_str(buf);
+                self.request_frame();
+            },
+        }
+        Ok(())
+    }
+
+    /// Sets the window title, saving the previous one first if not saved yet.
+    pub async fn set_title(&self, title: &str) -> Result<(), Error> {
+        let mut buf = String::new();
+        {
+            let mut curr_title = self.title();
+            if curr_title.is_none() {
+                stdio::push_title(&mut buf)?;
+            }
+            stdio::set_title(&mut buf, title)?;
+            *curr_title = Some(title.to_owned());
+        }
+        self.write_sequences(&buf).await
+    }
+
+    /// Restores the window title previous to [`ScreenData::set_title`], if it
+    /// was called.
+    pub async fn restore_title(&self) -> Result<(), Error> {
         let mut buf = String::new();
-        save_screen(&mut buf)?;
+        if self.title().take().is_some() {
+            stdio::pop_title(&mut buf)?;
+        }
+        self.write_sequences(&buf).await
+    }
+
+    /// Rings the terminal's bell.
+    pub async fn bell(&self) -> Result<(), Error> {
+        self.write_sequences("\x07").await
+    }
+
+    /// Emits a desktop notification with the given protocol.
+    pub async fn notify_desktop(
+        &self,
+        protocol: NotifyProtocol,
+        title: &str,
+        body: &str,
+    ) -> Result<(), Error> {
+        let mut buf = String::new();
+        stdio::notification(&mut buf, protocol, title, body)?;
+        self.write_sequences(&buf).await
+    }
+
+    /// Writes the sequence setting up the terminal (alternate screen or
+    /// reserved rows, colors, hidden cursor, input modes) into the buffer. The
+    /// cursor is left at the top-left corner of the screen.
+    fn write_setup(&self, buf: &mut String) -> fmt::Result {
+        let clear = match self.inline {
+            Some(rows) => {
+                // Scrolls the terminal if there are not enough rows below.
+                buf.push('\r');
+                for _ in 1 .. rows {
+                    buf.push('\n');
+                }
+                if rows > 1 {
+                    write!(buf, "{}", crossterm::cursor::MoveUp(rows - 1))?;
+                }
+                crossterm::terminal::ClearType::FromCursorDown
+            },
+            None => {
+                save_screen(buf)?;
+                write!(buf, "{}", crossterm::cursor::MoveTo(0, 0))?;
+                crossterm::terminal::ClearType::All
+            },
+        };
         write!(
             buf,
             "{}{}{}{}",
             crossterm::style::SetBackgroundColor(
-                crossterm::style::Color::Black
+                self.color_support.to_crossterm(BasicColor::Black.into())
             ),
             crossterm::style::SetForegroundColor(
-                crossterm::style::Color::White
+                self.color_support.to_crossterm(BasicColor::White.into())
             ),
             crossterm::cursor::Hide,
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+            crossterm::terminal::Clear(clear),
         )?;
+        if let Some(title) = &*self.title() {
+            stdio::push_title(buf)?;
+            stdio::set_title(buf, title)?;
+        }
+        self.modes.enable(buf)
+    }
+
+    /// Writes the sequence undoing [`ScreenData::write_setup`] into the
+    /// buffer. When rendering inline, the cursor is left in the line after
+    /// the screen, which is kept in the scrollback; if the buffer is not
+    /// given, the cursor is assumed to be at the top of the screen.
+    fn write_cleanup(
+        &self,
+        buf: &mut String,
+        buffer: Option<&ScreenBuffer>,
+    ) -> fmt::Result {
+        buf.push_str(&self.take_queued());
+        self.modes.disable(buf)?;
+        write!(buf, "{}", crossterm::cursor::Show)?;
+        let styled = match buffer {
+            Some(buffer) => buffer.term_cursor.is_styled(),
+            None => true,
+        };
+        if styled {
+            crossterm::cursor::SetCursorStyle::DefaultUserShape
+                .write_ansi(buf)?;
+        }
+        if self.title().is_some() {
+            stdio::pop_title(buf)?;
+        }
+        match self.inline {
+            Some(rows) => {
+                let (cursor, height) = match buffer {
+                    Some(buffer) => (buffer.cursor, buffer.size().y),
+                    None => (Vec2 { x: 0, y: 0 }, rows),
+                };
+                let bottom = Vec2 { x: 0, y: height.saturating_sub(1) };
+                self.write_move(buf, cursor, bottom)?;
+                write!(
+                    buf,
+                    "{}{}\r\n",
+                    crossterm::style::SetAttribute(
+                        crossterm::style::Attribute::Reset
+                    ),
+                    crossterm::style::ResetColor,
+                )
+            },
+            None => restore_screen(buf),
+        }
+    }
+
+    /// Writes a movement of the cursor between two points of the screen. When
+    /// rendering inline, rows are moved relatively, since the position of the
+    /// screen in the terminal is not known.
+    fn write_move(
+        &self,
+        buf: &mut String,
+        from: Vec2,
+        to: Vec2,
+    ) -> fmt::Result {
+        if self.inline.is_none() {
+            return write!(
+                buf,
+                "{}",
+                crossterm::cursor::MoveTo(
+                    coord::to_crossterm(to.x),
+                    coord::to_crossterm(to.y)
+                )
+            );
+        }
+        if to.y > from.y {
+            let rows = coord::to_crossterm(to.y - from.y);
+            write!(buf, "{}", crossterm::cursor::MoveDown(rows))?;
+        } else if to.y < from.y {
+            let rows = coord::to_crossterm(from.y - to.y);
+            write!(buf, "{}", crossterm::cursor::MoveUp(rows))?;
+        }
+        let column = coord::to_crossterm(to.x);
+        write!(buf, "{}", crossterm::cursor::MoveToColumn(column))
+    }
+
+    /// Writes the message asking the user to resize the screen to at least
+    /// the minimum size, clearing the screen from the given cursor position.
+    fn write_ask_resize(&self, buf: &mut String, cursor: Vec2) -> fmt::Result {
+        if self.inline.is_some() {
+            self.write_move(buf, cursor, Vec2 { x: 0, y: 0 })?;
+            write!(
+                buf,
+                "{}",
+                crossterm::terminal::Clear(
+                    crossterm::terminal::ClearType::FromCursorDown
+                ),
+            )?;
+        } else {
+            write!(
+                buf,
+                "{}{}",
+                crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+                crossterm::cursor::MoveTo(0, 0),
+            )?;
+        }
+        write!(buf, "RESIZE {}x{}", self.min_size.x, self.min_size.y)
+    }
+
+    /// Initialization of the terminal, such as cleaning the screen. The given
+    /// size is the actual size of the terminal.
+    pub async fn setup(&self, size: Vec2) -> Result<(), Error> {
+        let mut buf = String::new();
+        self.write_setup(&mut buf)?;
         self.stdout.write_and_flush(buf.as_bytes()).await?;
+        if let Some(recorder) = &self.recorder {
+            recorder.start(size);
+            recorder.output(&buf);
+        }
         Ok(())
     }
 
     /// Asynchronous cleanup. It is preferred to call this before dropping.
+    /// Does nothing if already cleaned up.
     pub async fn cleanup(&self) -> Result<(), Error> {
-        task::block_in_place(|| crossterm::terminal::disable_raw_mode())?;
+        if self.cleanedup.load(Acquire) {
+            return Ok(());
+        }
+        let mut screen = self.lock().await;
         let mut buf = String::new();
-        write!(buf, "{}", crossterm::cursor::Show)?;
-        restore_screen(&mut buf)?;
+        if self.inline.is_some() && screen.buffer.valid {
+            // The last frame is kept in the scrollback, so it must be complete.
+            screen.render(&mut buf).await?;
+            buf.clear();
+        }
+        task::block_in_place(|| self.backend.disable_raw_mode())?;
+        self.write_cleanup(&mut buf, Some(&screen.buffer))?;
+        drop(screen);
         self.stdout.write_and_flush(buf.as_bytes()).await?;
+        if let Some(recorder) = &self.recorder {
+            task::block_in_place(|| recorder.finish());
+        }
         self.cleanedup.store(true, Release);
         Ok(())
     }
+
+    /// Best-effort blocking cleanup, with the same sequence as
+    /// [`ScreenData::cleanup`], for when the asynchronous one cannot be used
+    /// (e.g. when dropping or panicking). Does nothing if already cleaned up.
+    pub fn cleanup_blocking(&self) {
+        // A frame being written is waited for, for a short time, since the
+        // lock might be held by the panicking task itself. Frames are not
+        // written once this is cleaned up.
+        let deadline = Instant::now() + Duration::from_millis(100);
+        let _stdout = loop {
+            match self.stdout.try_lock() {
+                Some(stdout) => break Some(stdout),
+                None if Instant::now() >= deadline => break None,
+                None => thread::sleep(Duration::from_millis(1)),
+            }
+        };
+        if !self.cleanedup.swap(true, AcqRel) {
+            let _ = self.backend.disable_raw_mode();
+            let mut buf = String::new();
+            let buffer = self.buffer.try_lock().ok();
+            if self.write_cleanup(&mut buf, buffer.as_deref()).is_ok() {
+                let _ = self.backend.write_blocking(buf.as_bytes());
+            }
+            if let Some(recorder) = &self.recorder {
+                recorder.finish();
+            }
+        }
+    }
 }
 
 impl Drop for ScreenData {
     fn drop(&mut self) {
-        if !self.cleanedup.load(Relaxed) {
-            let _ = crossterm::terminal::disable_raw_mode();
-            let mut buf = String::new();
-            write!(buf, "{}", crossterm::cursor::Show)
-                .ok()
-                .and_then(|_| stdio::restore_screen(&mut buf).ok())
-                .map(|_| println!("{}", buf));
-        }
+        self.cleanup_blocking();
     }
 }
 
@@ -175,11 +631,22 @@ impl<'terminal> Screen<'terminal> {
         self.data.min_size
     }
 
+    /// Returns the colors supported by the terminal. Other colors are
+    /// approximated when rendered.
+    pub fn color_support(&self) -> ColorSupport {
+        self.data.color_support
+    }
+
     /// Applies an update function to a [`Tile`]. An update function gets access
     /// to a mutable reference of a [`Tile`], updates it, and then the screen
     /// handles any changes made to it. A regular [`Tile`] can be used as an
     /// updater, in which the case a simple replacement is made. This operation
     /// is buffered.
+    ///
+    /// A wide grapheme (see [`TermGrapheme::width`]) also occupies the tile at
+    /// its right, which becomes a blank continuation. Setting either half of a
+    /// wide grapheme replaces the other half by a blank space. A wide grapheme
+    /// set at the last column is replaced by a blank space, as it does not fit.
     pub fn set<T>(&mut self, point: Vec2, updater: T)
     where
         T: tile::Updater,
@@ -188,12 +655,7 @@ impl<'terminal> Screen<'terminal> {
             .buffer
             .make_index(point)
             .unwrap_or_else(|| out_of_bounds(point, self.buffer.size()));
-        updater.update(&mut self.buffer.curr[index]);
-        if self.buffer.old[index] != self.buffer.curr[index] {
-            self.buffer.changed.insert(point);
-        } else {
-            self.buffer.changed.remove(&point);
-        }
+        self.buffer.update(index, updater);
     }
 
     /// Gets the attributes of a given [`Tile`], regardless of being flushed to
@@ -206,12 +668,50 @@ impl<'terminal> Screen<'terminal> {
         &self.buffer.curr[index]
     }
 
+    /// Shows the terminal's cursor at the given point, or hides it if `None`
+    /// (default). The cursor is placed after the tiles are drawn, in every
+    /// frame; it is hidden while the point is out of the screen, e.g. after a
+    /// resize.
+    ///
+    /// # Panics
+    /// Panics if the point is out of bounds.
+    pub fn set_cursor(&mut self, point: Option<Vec2>) {
+        if let Some(point) = point {
+            if self.buffer.make_index(point).is_none() {
+                out_of_bounds(point, self.buffer.size());
+            }
+        }
+        self.buffer.app_cursor.pos = point;
+    }
+
+    /// Returns where the terminal's cursor is shown, if anywhere.
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.buffer.app_cursor.pos
+    }
+
+    /// Sets the shape of the terminal's cursor, and whether it blinks
+    /// (default: a blinking block). The terminal's usual style is restored
+    /// when the terminal is cleaned up.
+    pub fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) {
+        self.buffer.app_cursor.shape = shape;
+        self.buffer.app_cursor.blinking = blinking;
+    }
+
+    /// Takes a snapshot of every [`Tile`] in the screen, regardless of being
+    /// flushed to the screen yet or not, and of where the cursor is shown.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(self.buffer.size(), self.buffer.curr.clone())
+            .with_cursor(self.cursor())
+    }
+
     /// Sets every [`Tile`] into a whitespace grapheme with the given color.
     pub fn clear(&mut self, background: Color) {
         let size = self.buffer.size();
         let tile = Tile {
             colors: Color2 { background, ..Color2::default() },
             grapheme: TermGrapheme::space(),
+            attributes: Attributes::NONE,
+            link: None,
         };
 
         for y in 0 .. size.y {
@@ -232,81 +732,98 @@ impl<'terminal> Screen<'terminal> {
     where
         C: color::Updater,
     {
-        let mut len = tstring.count_graphemes();
-        let mut slice = tstring.index(..);
+        let graphemes = tstring.iter().collect::<Vec<_>>();
         let screen_size = self.buffer.size();
         let size = style.make_size(screen_size);
 
+        let mut start = 0;
         let mut cursor = Vec2 { x: 0, y: style.top_margin };
         let mut is_inside = cursor.y - style.top_margin < size.y;
 
-        while len > 0 && is_inside {
+        while start < graphemes.len() && is_inside {
             is_inside = cursor.y - style.top_margin + 1 < size.y;
             let width = coord::to_index(size.x);
-            let pos = self.find_break_pos(width, len, size, &slice, is_inside);
+            let remaining = &graphemes[start ..];
+            let (count, columns) =
+                self.find_break_pos(width, remaining, is_inside);
 
-            cursor.x = size.x - coord::from_index(pos);
+            cursor.x = size.x - coord::from_index(columns);
             cursor.x = cursor.x + style.left_margin - style.right_margin;
             cursor.x = cursor.x * style.align_numer / style.align_denom;
 
-            slice = slice.index(.. pos);
+            self.write_styled_slice(&remaining[.. count], &style, &mut cursor);
 
-            self.write_styled_slice(&slice, &style, &mut cursor);
+            // A grapheme wider than the whole line is skipped.
+            start += count.max(1);
 
-            if pos != len && !is_inside {
+            if start < graphemes.len() && !is_inside {
                 self.set(cursor, |tile: &mut Tile| {
                     let grapheme = TermGrapheme::new_lossy("…");
                     let colors = style.colors.update(tile.colors);
-                    *tile = Tile { grapheme, colors };
+                    let attributes = style.attributes;
+                    let link = style.link.clone();
+                    *tile = Tile { grapheme, colors, attributes, link };
                 });
             }
 
             cursor.y += 1;
-            len -= pos;
         }
         cursor.y
     }
 
-    /// Finds the position where a line should break in a styled text.
+    /// Finds the position where a line should break in a styled text, given
+    /// the line's width in columns and the graphemes not written yet. Returns
+    /// how many graphemes fit in the line and how many columns they take. If
+    /// this is the last line (i.e. not `is_inside`), a column is reserved for
+    /// an ellipsis in case the text does not fit.
     fn find_break_pos(
         &self,
         width: usize,
-        total_graphemes: usize,
-        term_size: Vec2,
-        slice: &TermString,
+        graphemes: &[TermGrapheme],
         is_inside: bool,
-    ) -> usize {
-        if width <= slice.len() {
-            let mut pos = slice
-                .index(.. coord::to_index(term_size.x))
-                .iter()
-                .rev()
-                .position(|grapheme| grapheme == TermGrapheme::space())
-                .map_or(width, |rev| total_graphemes - rev);
-            if !is_inside {
-                pos -= 1;
+    ) -> (usize, usize) {
+        let total = graphemes.iter().map(TermGrapheme::width).sum::<usize>();
+        if total <= width {
+            return (graphemes.len(), total);
+        }
+
+        let available = if is_inside { width } else { width.saturating_sub(1) };
+        let mut columns = 0;
+        let mut count = 0;
+        let mut last_space = None;
+        for grapheme in graphemes {
+            let grapheme_width = grapheme.width();
+            if columns + grapheme_width > available {
+                break;
+            }
+            columns += grapheme_width;
+            count += 1;
+            if *grapheme == TermGrapheme::space() {
+                last_space = Some((count, columns));
             }
-            pos
-        } else {
-            total_graphemes
         }
+
+        last_space.unwrap_or((count, columns))
     }
 
     /// Writes a slice using the given style. It should fit in one line.
     fn write_styled_slice<C>(
         &mut self,
-        slice: &TermString,
+        slice: &[TermGrapheme],
         style: &Style<C>,
         cursor: &mut Vec2,
     ) where
         C: color::Updater,
     {
         for grapheme in slice {
+            let width = grapheme.width();
             self.set(*cursor, |tile: &mut Tile| {
-                tile.grapheme = grapheme;
+                tile.grapheme = grapheme.clone();
                 tile.colors = style.colors.update(tile.colors);
+                tile.attributes = style.attributes;
+                tile.link = style.link.clone();
             });
-            cursor.x += 1;
+            cursor.x += coord::from_index(width);
         }
     }
 
@@ -317,13 +834,14 @@ impl<'terminal> Screen<'terminal> {
         &mut self,
         new_size: Vec2,
         guard: &mut Option<LockedStdout<'terminal>>,
-    ) -> io::Result<()> {
+    ) -> Result<(), Error> {
+        self.data.record_resize(new_size);
         let min_size = self.data.min_size;
         if new_size.x < min_size.x || new_size.y < min_size.y {
             if guard.is_none() {
                 self.buffer.valid = false;
                 let mut stdout = self.data.stdout.lock().await;
-                self.ask_resize(&mut stdout, min_size).await?;
+                self.ask_resize(&mut stdout).await?;
                 *guard = Some(stdout);
             }
         } else {
@@ -333,6 +851,7 @@ impl<'terminal> Screen<'terminal> {
             };
             self.buffer.valid = true;
             self.resize(new_size, &mut stdout).await?;
+            self.data.request_frame();
         }
 
         Ok(())
@@ -342,17 +861,12 @@ impl<'terminal> Screen<'terminal> {
     async fn ask_resize(
         &mut self,
         stdout: &mut LockedStdout<'terminal>,
-        min_size: Vec2,
-    ) -> io::Result<()> {
-        let buf = format!(
-            "{}{}RESIZE {}x{}",
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-            crossterm::cursor::MoveTo(0, 0),
-            min_size.x,
-            min_size.y,
-        );
-
+    ) -> Result<(), Error> {
+        let mut buf = String::new();
+        self.data.write_ask_resize(&mut buf, self.buffer.cursor)?;
         stdout.write_and_flush(buf.as_bytes()).await?;
+        self.data.record_output(&buf);
+        self.buffer.cursor.y = 0;
 
         Ok(())
     }
@@ -362,138 +876,308 @@ impl<'terminal> Screen<'terminal> {
         &mut self,
         new_size: Vec2,
         stdout: &mut LockedStdout<'terminal>,
-    ) -> io::Result<()> {
-        let buf = format!(
-            "{}{}{}",
+    ) -> Result<(), Error> {
+        let origin = Vec2 { x: 0, y: 0 };
+        let mut buf = format!(
+            "{}{}",
             crossterm::style::SetForegroundColor(
-                crossterm::style::Color::White
+                self.data.color_support.to_crossterm(BasicColor::White.into())
             ),
             crossterm::style::SetBackgroundColor(
-                crossterm::style::Color::Black
+                self.data.color_support.to_crossterm(BasicColor::Black.into())
             ),
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
         );
+        let clear = if self.data.inline.is_some() {
+            self.data.write_move(&mut buf, self.buffer.cursor, origin)?;
+            crossterm::terminal::ClearType::FromCursorDown
+        } else {
+            write!(buf, "{}", crossterm::cursor::MoveTo(0, 0))?;
+            crossterm::terminal::ClearType::All
+        };
+        write!(buf, "{}", crossterm::terminal::Clear(clear))?;
         stdout.write_and_flush(buf.as_bytes()).await?;
+        self.data.record_output(&buf);
+        self.buffer.cursor = origin;
         self.buffer.resize(new_size);
 
         Ok(())
     }
 
+    /// Suspends the process, as a shell does with Ctrl-Z: the terminal is
+    /// restored, the process is stopped through the backend, and once it is
+    /// continued, the terminal is set up again and the whole screen is redrawn
+    /// in the next frame.
+    pub(crate) async fn suspend(&mut self) -> Result<(), Error> {
+        let data = self.data;
+        self.while_restored(|backend| {
+            if data.listens_continued() {
+                data.stopped_itself.store(true, Release);
+            }
+            let result = backend.suspend();
+            if result.is_err() {
+                data.stopped_itself.store(false, Release);
+            }
+            result
+        })
+        .await
+    }
+
+    /// Restores the terminal (raw mode, alternate screen, input modes...) to
+    /// the state before setup, runs the given blocking action, and sets the
+    /// terminal up again, even if the action fails. The whole screen is
+    /// redrawn in the next frame.
+    pub(crate) async fn while_restored<F, T>(
+        &mut self,
+        action: F,
+    ) -> Result<T, Error>
+    where
+        F: FnOnce(&dyn Backend) -> io::Result<T>,
+    {
+        // The stdout is locked for the whole section, so that nothing else
+        // (e.g. a window title) is written to the restored terminal. While the
+        // size is invalid, the reactor keeps it locked instead, and writes to
+        // it only through the screen, which is held here.
+        let data = self.data;
+        let _stdout = if self.buffer.valid {
+            Some(data.stdout.lock().await)
+        } else {
+            None
+        };
+        let mut buf = String::new();
+        data.write_cleanup(&mut buf, Some(&self.buffer))?;
+        task::block_in_place(|| {
+            data.backend.disable_raw_mode()?;
+            data.backend.write_blocking(buf.as_bytes())
+        })?;
+        data.record_output(&buf);
+        // When inline, the cleanup leaves the cursor in the line after the
+        // screen, where the screen is set up again.
+        self.buffer.cursor = Vec2 { x: 0, y: 0 };
+
+        let result = task::block_in_place(|| action(&*data.backend));
+        self.set_up_again()?;
+        Ok(result?)
+    }
+
+    /// Sets the terminal up again after the process was stopped and continued
+    /// by something other than [`Screen::suspend`] (e.g. `kill -STOP`), since
+    /// the shell may have restored the terminal meanwhile. The whole screen is
+    /// redrawn in the next frame.
+    pub(crate) async fn resume(&mut self) -> Result<(), Error> {
+        // Locked as in `while_restored`.
+        let _stdout = if self.buffer.valid {
+            Some(self.data.stdout.lock().await)
+        } else {
+            None
+        };
+        self.set_up_again()
+    }
+
+    /// Sets the terminal up (raw mode, alternate screen, input modes...),
+    /// writing to the backend directly, and invalidates the buffer, so that
+    /// the whole screen is redrawn in the next frame. The stdout must not be
+    /// written by anything else meanwhile.
+    fn set_up_again(&mut self) -> Result<(), Error> {
+        let data = self.data;
+        let origin = Vec2 { x: 0, y: 0 };
+        let mut buf = String::new();
+        if data.inline.is_some() {
+            // Rows are reserved again from the top of the screen, rather than
+            // from wherever the cursor was left.
+            data.write_move(&mut buf, self.buffer.cursor, origin)?;
+        }
+        data.write_setup(&mut buf)?;
+        if !self.buffer.valid {
+            data.write_ask_resize(&mut buf, origin)?;
+        }
+        task::block_in_place(|| {
+            data.backend.enable_raw_mode()?;
+            data.backend.write_blocking(buf.as_bytes())
+        })?;
+        data.record_output(&buf);
+        self.buffer.cursor = origin;
+        self.buffer.term_cursor = CursorState::hidden();
+        self.buffer.invalidate();
+        Ok(())
+    }
+
     /// Renders the buffer into the screen using the referred terminal.
     pub(crate) async fn render(
         &mut self,
         buf: &mut String,
     ) -> Result<(), Error> {
-        let screen_size = self.buffer.size();
+        let start = Instant::now();
+        let changed_tiles = self.buffer.changed.len();
         buf.clear();
+        if self.data.synchronized {
+            crossterm::terminal::BeginSynchronizedUpdate.write_ansi(buf)?;
+        }
 
-        let mut colors = Color2::default();
-        let mut cursor = Vec2 { x: 0, y: 0 };
-        self.render_init_term(buf, colors, cursor)?;
+        let mut frame = Frame::new(
+            &self.buffer,
+            self.data.color_support,
+            self.data.inline.is_some(),
+            self.data.erase_lines,
+            self.data.repeat_graphemes,
+        );
+        frame.draw(buf)?;
+        let term_cursor = self.render_cursor(buf, &mut frame)?;
+        let cursor = frame.cursor();
 
-        for &coord in self.buffer.changed.iter() {
-            self.render_tile(
-                buf,
-                &mut colors,
-                &mut cursor,
-                screen_size,
-                coord,
-            )?;
+        if self.data.synchronized {
+            crossterm::terminal::EndSynchronizedUpdate.write_ansi(buf)?;
         }
+        let diff_time = start.elapsed();
 
-        if let Some(mut stdout) = self.data.stdout.try_lock() {
+        // If the frame cannot be written now, the changes are kept, and the
+        // frame is requested again when this screen is dropped.
+        let written = self.data.stdout.try_lock();
+        if self.data.cleanedup.load(Acquire) {
+            // Restored meanwhile, e.g. by the panic hook.
+            return Ok(());
+        }
+        let dropped = written.is_none();
+        if let Some(mut stdout) = written {
+            buf.insert_str(0, &self.data.take_queued());
             stdout.write_and_flush(buf.as_bytes()).await?;
+            self.data.record_output(buf);
+            self.buffer.cursor = cursor;
+            self.buffer.term_cursor = term_cursor;
+            self.buffer.next_tick();
         }
 
-        self.buffer.next_tick();
+        self.data.stats.frame(FrameStats {
+            diff_time,
+            bytes: if dropped { 0 } else { buf.len() },
+            changed_tiles,
+            app_lock_time: Duration::ZERO,
+            dropped,
+        });
 
         Ok(())
     }
 
-    /// Initializes terminal state into the buffer before rendering.
-    fn render_init_term(
+    /// Applies the cursor set by the application, after the tiles are drawn.
+    /// Returns the state of the cursor in the terminal after the frame.
+    fn render_cursor(
         &self,
         buf: &mut String,
-        colors: Color2,
-        cursor: Vec2,
-    ) -> Result<(), Error> {
-        write!(
-            buf,
-            "{}{}{}",
-            crossterm::style::SetForegroundColor(
-                colors.foreground.to_crossterm()
-            ),
-            crossterm::style::SetBackgroundColor(
-                colors.background.to_crossterm()
-            ),
-            crossterm::cursor::MoveTo(
-                coord::to_crossterm(cursor.x),
-                coord::to_crossterm(cursor.y)
-            ),
-        )?;
-
-        Ok(())
-    }
+        frame: &mut Frame,
+    ) -> Result<CursorState, Error> {
+        let applied = self.buffer.term_cursor;
+        let mut state = self.buffer.app_cursor;
+        // The screen may have shrunk since the cursor was set.
+        state.pos =
+            state.pos.filter(|pos| self.buffer.make_index(*pos).is_some());
 
-    /// Renders a single tile in the given coordinate.
-    fn render_tile(
-        &self,
-        buf: &mut String,
-        colors: &mut Color2,
-        cursor: &mut Vec2,
-        screen_size: Vec2,
-        coord: Vec2,
-    ) -> Result<(), Error> {
-        if *cursor != coord {
-            write!(
-                buf,
-                "{}",
-                crossterm::cursor::MoveTo(
-                    coord::to_crossterm(coord.x),
-                    coord::to_crossterm(coord.y)
-                )
-            )?;
+        match state.pos {
+            Some(pos) => {
+                if !state.same_style(&applied) {
+                    state.style_command().write_ansi(buf)?;
+                }
+                frame.move_to(buf, pos)?;
+                if applied.pos.is_none() {
+                    write!(buf, "{}", crossterm::cursor::Show)?;
+                }
+                Ok(state)
+            },
+            None => {
+                if applied.pos.is_some() {
+                    write!(buf, "{}", crossterm::cursor::Hide)?;
+                }
+                Ok(CursorState { pos: None, ..applied })
+            },
         }
-        *cursor = coord;
-
-        let tile = self.get(*cursor);
-        if colors.background != tile.colors.background {
-            let color = tile.colors.background.to_crossterm();
-            write!(buf, "{}", crossterm::style::SetBackgroundColor(color))?;
-        }
-        if colors.foreground != tile.colors.foreground {
-            let color = tile.colors.foreground.to_crossterm();
-            write!(buf, "{}", crossterm::style::SetForegroundColor(color))?;
-        }
-        *colors = tile.colors;
-
-        write!(buf, "{}", tile.grapheme)?;
+    }
+}
 
-        if cursor.x <= screen_size.x {
-            cursor.x += 1;
+impl<'terminal> Drop for Screen<'terminal> {
+    fn drop(&mut self) {
+        if self.buffer.is_pending() {
+            self.data.request_frame();
         }
+    }
+}
 
-        Ok(())
+/// The size of the screen given the number of rows reserved when rendering
+/// inline, if so, and the actual size of the terminal.
+pub(crate) fn viewport(inline: Option<Coord>, term_size: Vec2) -> Vec2 {
+    match inline {
+        Some(rows) => Vec2 { x: term_size.x, y: rows.min(term_size.y) },
+        None => term_size,
     }
 }
 
 /// The renderer loop. Should be called only when setting up a terminal handler.
-/// Exits on error or when notified that it should exit.
+/// Renders a frame only when requested, i.e. when a screen with pending changes
+/// is dropped, or on resize. Exits on error or when notified that it should
+/// exit.
 pub(crate) async fn renderer(shared: &Shared) -> Result<(), Error> {
-    let mut interval = time::interval(shared.screen().frame_time);
+    if let Some(clock) = shared.screen().clock() {
+        return clocked_renderer(shared, clock).await;
+    }
     let mut buf = String::new();
 
     loop {
+        let disconnected = shared.screen().subscribe();
+        if !shared.is_connected() {
+            break;
+        }
+        tokio::select! {
+            _ = shared.screen().frame_requested() => (),
+            _ = disconnected => break,
+        };
+
         {
             let _guard = shared.service_guard().await?;
             let mut screen = shared.screen().lock().await;
-            screen.render(&mut buf).await?;
+            // While the screen is too small, the stdout is held for the
+            // resize message; a frame is requested again once resized.
+            if screen.valid_size() {
+                screen.render(&mut buf).await?;
+            }
         }
 
+        // The frame time bounds the frame rate: changes made meanwhile are
+        // rendered together in the next frame.
+        let disconnected = shared.screen().subscribe();
+        tokio::select! {
+            _ = time::sleep(shared.screen().curr_frame_time()) => (),
+            _ = disconnected => break,
+        };
+    }
+
+    Ok(())
+}
+
+/// The renderer loop with the fixed frame clock: ticks the clock once per
+/// frame time, and renders a frame at each tick only if there are changes and
+/// the screen is not too small.
+/// Late ticks are delayed rather than made up for.
+async fn clocked_renderer(
+    shared: &Shared,
+    clock: &FrameClock,
+) -> Result<(), Error> {
+    let mut buf = String::new();
+    let mut interval = time::interval(shared.screen().frame_time);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let disconnected = shared.screen().subscribe();
+        if !shared.is_connected() {
+            break;
+        }
         tokio::select! {
             _ = interval.tick() => (),
-            _ = shared.screen().subscribe() => break,
+            _ = disconnected => break,
         };
+
+        clock.tick();
+        let _guard = shared.service_guard().await?;
+        let mut screen = shared.screen().lock().await;
+        if screen.valid_size() && screen.buffer.is_pending() {
+            screen.render(&mut buf).await?;
+        }
     }
 
     Ok(())
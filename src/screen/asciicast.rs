@@ -0,0 +1,113 @@
+//! This module defines a recorder of the terminal's output in the asciicast v2
+//! format, as used by asciinema.
+
+use crate::{coord::Vec2, recording::Sink};
+use std::{
+    io::{self, Write},
+    sync::{Mutex, PoisonError},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Records the output written to the terminal, and its resizes, as an
+/// asciicast v2 file: a JSON header followed by one JSON event per line.
+/// Recording is best-effort: the file is written in the background, and
+/// errors only stop the recording.
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    /// Where the recording is written to.
+    sink: Sink,
+    /// When the recording started (i.e. when the header was written).
+    start: Mutex<Option<Instant>>,
+    /// Buffer reused to escape output events, so that only the finished line
+    /// is copied for the sink.
+    line: Mutex<Vec<u8>>,
+}
+
+impl Recorder {
+    /// Creates a recorder that writes to the given writer. Nothing is written
+    /// until [`Recorder::start`] is called.
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self {
+            sink: Sink::new("asciicast recording", writer),
+            start: Mutex::new(None),
+            line: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Starts the recording by writing the header, with the given initial
+    /// size of the terminal. Event timestamps are relative to this call.
+    pub fn start(&self, size: Vec2) {
+        self.sink.start();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let header = format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": \
+             {}}}\n",
+            size.x, size.y, timestamp
+        );
+        self.sink.write(header.into_bytes());
+        *self.start.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Records bytes written to the terminal as an output event.
+    pub fn output(&self, data: &str) {
+        let elapsed = self.elapsed();
+        let mut line = self.line.lock().unwrap_or_else(PoisonError::into_inner);
+        line.clear();
+        let _ = write!(line, "[{:.6}, \"o\", \"", elapsed);
+        let _ = write_escaped(&mut *line, data);
+        line.extend_from_slice(b"\"]\n");
+        self.sink.write(line.to_vec());
+    }
+
+    /// Records a resize of the terminal as a resize event.
+    pub fn resize(&self, size: Vec2) {
+        let event = format!(
+            "[{:.6}, \"r\", \"{}x{}\"]\n",
+            self.elapsed(),
+            size.x,
+            size.y
+        );
+        self.sink.write(event.into_bytes());
+    }
+
+    /// Finishes the recording, waiting for it to be written and flushed.
+    pub fn finish(&self) {
+        self.sink.finish();
+    }
+
+    /// Seconds elapsed since the recording started.
+    fn elapsed(&self) -> f64 {
+        let start = *self.start.lock().unwrap();
+        start.map_or(0.0, |start| start.elapsed().as_secs_f64())
+    }
+}
+
+/// Writes the given data as the contents of a JSON string, escaping quotes,
+/// backslashes and control characters. Unescaped runs are written at once.
+pub(crate) fn write_escaped<W>(writer: &mut W, data: &str) -> io::Result<()>
+where
+    W: Write + ?Sized,
+{
+    let mut start = 0;
+    for (index, ch) in data.char_indices() {
+        let needs_escape = ch == '"' || ch == '\\' || ch.is_control();
+        if needs_escape {
+            writer.write_all(&data.as_bytes()[start .. index])?;
+            match ch {
+                '"' => writer.write_all(b"\\\"")?,
+                '\\' => writer.write_all(b"\\\\")?,
+                '\n' => writer.write_all(b"\\n")?,
+                '\r' => writer.write_all(b"\\r")?,
+                '\t' => writer.write_all(b"\\t")?,
+                _ => write!(writer, "\\u{:04x}", u32::from(ch))?,
+            }
+            start = index + ch.len_utf8();
+        }
+    }
+    writer.write_all(&data.as_bytes()[start ..])
+}
@@ -0,0 +1,36 @@
+//! This module exposes the screen buffer to the benchmarks, behind the
+//! `bench-internals` feature. It is not part of the public API and may change
+//! at any time.
+
+use crate::{coord::Vec2, screen::buffer::ScreenBuffer, tile::Tile};
+
+/// A screen buffer, updated and advanced as the screen and the renderer do.
+#[derive(Debug)]
+pub struct Buffer {
+    inner: ScreenBuffer,
+}
+
+impl Buffer {
+    /// A blank buffer of the given size.
+    pub fn blank(size: Vec2) -> Self {
+        Self { inner: ScreenBuffer::blank(size) }
+    }
+
+    /// Sets the tile at the given point, recording the change.
+    pub fn set(&mut self, point: Vec2, tile: &Tile) {
+        if let Some(index) = self.inner.make_index(point) {
+            self.inner.update(index, |curr: &mut Tile| curr.clone_from(tile));
+        }
+    }
+
+    /// Visits the changed tiles, as when rendering, and advances to the next
+    /// frame. Returns the total width of the visited graphemes.
+    pub fn frame(&mut self) -> usize {
+        let mut visited = 0;
+        for index in self.inner.changed.iter() {
+            visited += self.inner.curr[index].grapheme.width();
+        }
+        self.inner.next_tick();
+        visited
+    }
+}
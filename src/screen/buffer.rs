@@ -1,7 +1,13 @@
 //! This module defines the screen (double) buffer and related items.
 
-use crate::{coord, coord::Vec2, tile::Tile};
-use std::collections::BTreeSet;
+use crate::{
+    coord,
+    coord::Vec2,
+    screen::{cursor::CursorState, damage::Damage},
+    string::TermGrapheme,
+    tile::{self, Tile},
+};
+use std::mem;
 
 /// The (double) buffer of the buffer of a screen.
 #[derive(Debug)]
@@ -14,8 +20,15 @@ pub struct ScreenBuffer {
     pub old: Vec<Tile>,
     /// Currently editing screen.
     pub curr: Vec<Tile>,
-    /// List of changed tiles.
-    pub changed: BTreeSet<Vec2>,
+    /// Indices of the changed tiles.
+    pub changed: Damage,
+    /// Where the terminal's cursor was left by the last output, relative to
+    /// the screen.
+    pub cursor: Vec2,
+    /// The cursor as set by the application.
+    pub app_cursor: CursorState,
+    /// The cursor as last applied to the terminal.
+    pub term_cursor: CursorState,
 }
 
 impl ScreenBuffer {
@@ -23,12 +36,16 @@ impl ScreenBuffer {
     pub fn blank(size: Vec2) -> Self {
         let curr = vec![Tile::default(); coord::to_index(size.y * size.x)];
         let old = curr.clone();
+        let width = coord::to_index(size.x);
         Self {
-            width: coord::to_index(size.x),
+            width,
             valid: true,
+            changed: Damage::new(width, curr.len()),
             curr,
             old,
-            changed: BTreeSet::new(),
+            cursor: Vec2 { x: 0, y: 0 },
+            app_cursor: CursorState::hidden(),
+            term_cursor: CursorState::hidden(),
         }
     }
 
@@ -50,15 +67,39 @@ impl ScreenBuffer {
         }
 
         self.width = coord::to_index(size.x);
-        self.changed.clear();
+        self.changed.resize(self.width, new_size);
     }
 
     /// Advances the buffer in one tick. I.e., `old` is discarded, `curr`
-    /// becomes both `curr` and `old`.
+    /// becomes both `curr` and `old`. The buffers are swapped, and only the
+    /// changed tiles are copied back into `curr`, since the others are
+    /// already the same.
     pub fn next_tick(&mut self) {
+        mem::swap(&mut self.old, &mut self.curr);
+        for index in self.changed.iter() {
+            self.curr[index].clone_from(&self.old[index]);
+        }
         self.changed.clear();
-        let (old, curr) = (&mut self.old, &self.curr);
-        old.clone_from(curr);
+    }
+
+    /// Whether the next frame has anything to render: changed tiles, or a
+    /// cursor different from the terminal's.
+    pub fn is_pending(&self) -> bool {
+        let pos =
+            self.app_cursor.pos.filter(|pos| self.make_index(*pos).is_some());
+        let cursor_pending = pos != self.term_cursor.pos
+            || pos.is_some() && !self.app_cursor.same_style(&self.term_cursor);
+        !self.changed.is_empty() || cursor_pending
+    }
+
+    /// Forces every tile to be rendered again in the next frame, e.g. after the
+    /// terminal was set up again and cleared. `old` becomes a blank screen.
+    pub fn invalidate(&mut self) {
+        let default_tile = Tile::default();
+        for tile in &mut self.old {
+            *tile = default_tile.clone();
+        }
+        self.changed.fill();
     }
 
     /// Size of the buffer in coordinates.
@@ -73,6 +114,85 @@ impl ScreenBuffer {
         }
     }
 
+    /// Makes a coordinate from an index.
+    pub fn make_point(&self, index: usize) -> Vec2 {
+        Vec2 {
+            x: coord::from_index(index % self.width),
+            y: coord::from_index(index / self.width),
+        }
+    }
+
+    /// Whether the tile at the given index is the continuation (right half) of
+    /// a wide grapheme in the currently editing screen.
+    pub fn is_continuation(&self, index: usize) -> bool {
+        Self::continues(&self.curr, self.width, index)
+    }
+
+    /// Applies an updater to the tile at the given index and records the
+    /// change. Wide graphemes are kept consistent: a wide grapheme occupies the
+    /// tile after it as a continuation, and overwriting either half of a wide
+    /// grapheme invalidates the other half.
+    pub fn update<T>(&mut self, index: usize, updater: T)
+    where
+        T: tile::Updater,
+    {
+        if self.is_continuation(index) {
+            // Writing over the right half breaks the wide grapheme.
+            self.curr[index - 1].grapheme = TermGrapheme::space();
+            self.mark(index - 1);
+        }
+
+        let was_wide = self.curr[index].grapheme.width() > 1;
+        updater.update(&mut self.curr[index]);
+        let mut is_wide = self.curr[index].grapheme.width() > 1;
+        if is_wide && index % self.width + 1 == self.width {
+            // No room for the right half in the last column.
+            self.curr[index].grapheme = TermGrapheme::space();
+            is_wide = false;
+        }
+        self.mark(index);
+
+        let next = index + 1;
+        if is_wide {
+            let head = &self.curr[index];
+            self.curr[next] = Tile {
+                grapheme: TermGrapheme::space(),
+                colors: head.colors,
+                attributes: head.attributes,
+                link: head.link.clone(),
+            };
+            self.mark(next);
+            if next + 1 < self.curr.len() {
+                // In case the next tile was itself wide, its continuation is
+                // freed.
+                self.mark(next + 1);
+            }
+        } else if was_wide {
+            // The continuation is freed.
+            self.mark(next);
+        }
+    }
+
+    /// Records whether the tile at the given index changed since the last
+    /// tick, either in contents or in being a continuation.
+    fn mark(&mut self, index: usize) {
+        let changed = self.old[index] != self.curr[index]
+            || Self::continues(&self.old, self.width, index)
+                != Self::continues(&self.curr, self.width, index);
+        if changed {
+            self.changed.insert(index);
+        } else {
+            self.changed.remove(index);
+        }
+    }
+
+    /// Whether the tile at the given index of the given screen is the
+    /// continuation of a wide grapheme.
+    fn continues(tiles: &[Tile], width: usize, index: usize) -> bool {
+        let column = index % width;
+        column >= 1 && tiles[index - 1].grapheme.width() > 1
+    }
+
     /// Makes an index from a coordinate.
     pub fn make_index(&self, point: Vec2) -> Option<usize> {
         let x = coord::to_index(point.x);
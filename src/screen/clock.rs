@@ -0,0 +1,61 @@
+//! This module defines the fixed frame clock, which counts time in frames
+//! instead of by the wall clock.
+
+use std::sync::atomic::{AtomicU64, Ordering::*};
+use tokio::sync::Notify;
+
+/// A clock counting time in frames. The renderer ticks it once per frame time,
+/// and the event reactor then delivers the events of the new frame and marks
+/// it as ready, which is when the application sees it.
+#[derive(Debug, Default)]
+pub(crate) struct FrameClock {
+    /// Last frame ticked by the renderer. Frames start at 1.
+    ticked: AtomicU64,
+    /// Notifies the reactor of ticks. Ticks made while the reactor is busy
+    /// are not lost.
+    tick_notifier: Notify,
+    /// Last frame whose events were delivered.
+    ready: AtomicU64,
+    /// Notifies the application of ready frames.
+    ready_notifier: Notify,
+}
+
+impl FrameClock {
+    /// Creates a clock that has not ticked yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock by one frame.
+    pub fn tick(&self) {
+        self.ticked.fetch_add(1, AcqRel);
+        self.tick_notifier.notify_one();
+    }
+
+    /// Last frame ticked.
+    pub fn ticked(&self) -> u64 {
+        self.ticked.load(Acquire)
+    }
+
+    /// Waits until the clock ticks, unless it ticked since the last wait.
+    pub async fn tick_notified(&self) {
+        self.tick_notifier.notified().await
+    }
+
+    /// Marks the given frame as ready, its events delivered.
+    pub fn make_ready(&self, frame: u64) {
+        self.ready.store(frame, Release);
+        self.ready_notifier.notify_waiters();
+    }
+
+    /// Waits until the given frame is ready.
+    pub async fn wait_ready(&self, frame: u64) {
+        loop {
+            let notified = self.ready_notifier.notified();
+            if self.ready.load(Acquire) >= frame {
+                break;
+            }
+            notified.await;
+        }
+    }
+}
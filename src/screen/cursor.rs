@@ -0,0 +1,58 @@
+//! This module defines the terminal's cursor as controlled by the application.
+
+use crate::coord::Vec2;
+use crossterm::cursor::SetCursorStyle;
+
+/// Shape of the terminal's cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorShape {
+    /// A block over the whole tile.
+    #[default]
+    Block,
+    /// A line under the tile.
+    Underline,
+    /// A vertical bar at the left of the tile.
+    Bar,
+}
+
+/// State of the cursor: where it is shown, if anywhere, and how.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CursorState {
+    /// Position of the cursor, or `None` if hidden.
+    pub pos: Option<Vec2>,
+    /// Shape of the cursor.
+    pub shape: CursorShape,
+    /// Whether the cursor blinks.
+    pub blinking: bool,
+}
+
+impl CursorState {
+    /// A hidden cursor with the terminal's usual style (a blinking block).
+    pub fn hidden() -> Self {
+        Self { pos: None, shape: CursorShape::Block, blinking: true }
+    }
+
+    /// Whether the style differs from the terminal's usual style.
+    pub fn is_styled(&self) -> bool {
+        self.shape != CursorShape::Block || !self.blinking
+    }
+
+    /// Whether this state has the same style as the other.
+    pub fn same_style(&self, other: &Self) -> bool {
+        self.shape == other.shape && self.blinking == other.blinking
+    }
+
+    /// The command setting the style of the cursor.
+    pub fn style_command(&self) -> SetCursorStyle {
+        match (self.shape, self.blinking) {
+            (CursorShape::Block, true) => SetCursorStyle::BlinkingBlock,
+            (CursorShape::Block, false) => SetCursorStyle::SteadyBlock,
+            (CursorShape::Underline, true) => {
+                SetCursorStyle::BlinkingUnderScore
+            },
+            (CursorShape::Underline, false) => SetCursorStyle::SteadyUnderScore,
+            (CursorShape::Bar, true) => SetCursorStyle::BlinkingBar,
+            (CursorShape::Bar, false) => SetCursorStyle::SteadyBar,
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! This module defines how the changed tiles of a screen are tracked between
+//! frames.
+
+use std::{iter, ops::Range};
+
+/// Number of tiles tracked by a single word of the bitmap.
+const WORD_BITS: usize = 64;
+
+/// The tiles of a screen changed since the last frame ("damaged"), as a dirty
+/// bitmap for each row. Tiles are identified by their index in the screen.
+/// Marking and unmarking a tile is constant-time, and clearing or iterating
+/// only visits the rows with damage.
+#[derive(Debug, Clone, Default)]
+pub struct Damage {
+    /// Width of the screen.
+    width: usize,
+    /// Number of bitmap words in a row.
+    row_words: usize,
+    /// Bitmap of damaged tiles, `row_words` words per row.
+    bits: Vec<u64>,
+    /// Number of damaged tiles in each row.
+    rows: Vec<usize>,
+    /// Total number of damaged tiles.
+    len: usize,
+}
+
+impl Damage {
+    /// Tracks a screen with the given width and number of tiles, without
+    /// damage.
+    pub fn new(width: usize, tiles: usize) -> Self {
+        let mut this = Self::default();
+        this.resize(width, tiles);
+        this
+    }
+
+    /// Tracks a screen with the given new width and number of tiles. The
+    /// damage is cleared.
+    pub fn resize(&mut self, width: usize, tiles: usize) {
+        let height = tiles.checked_div(width).unwrap_or(0);
+        self.width = width;
+        self.row_words = width.div_ceil(WORD_BITS);
+        self.bits.clear();
+        self.bits.resize(self.row_words * height, 0);
+        self.rows.clear();
+        self.rows.resize(height, 0);
+        self.len = 0;
+    }
+
+    /// Number of damaged tiles.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no tile is damaged.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the tile at the given index is damaged.
+    pub fn contains(&self, index: usize) -> bool {
+        let (word, mask) = self.locate(index);
+        self.bits[word] & mask != 0
+    }
+
+    /// Marks the tile at the given index as damaged.
+    pub fn insert(&mut self, index: usize) {
+        let (word, mask) = self.locate(index);
+        if self.bits[word] & mask == 0 {
+            self.bits[word] |= mask;
+            self.rows[index / self.width] += 1;
+            self.len += 1;
+        }
+    }
+
+    /// Unmarks the tile at the given index, e.g. because it was changed back.
+    pub fn remove(&mut self, index: usize) {
+        let (word, mask) = self.locate(index);
+        if self.bits[word] & mask != 0 {
+            self.bits[word] &= !mask;
+            self.rows[index / self.width] -= 1;
+            self.len -= 1;
+        }
+    }
+
+    /// Marks every tile as damaged.
+    pub fn fill(&mut self) {
+        for row in 0 .. self.rows.len() {
+            let words = self.row_range(row);
+            for word in &mut self.bits[words] {
+                *word = !0;
+            }
+            let extra = self.row_words * WORD_BITS - self.width;
+            if extra > 0 {
+                // Bits past the width do not refer to any tile.
+                self.bits[(row + 1) * self.row_words - 1] >>= extra;
+            }
+            self.rows[row] = self.width;
+        }
+        self.len = self.width * self.rows.len();
+    }
+
+    /// Unmarks every tile.
+    pub fn clear(&mut self) {
+        for row in 0 .. self.rows.len() {
+            if self.rows[row] > 0 {
+                let words = self.row_range(row);
+                for word in &mut self.bits[words] {
+                    *word = 0;
+                }
+                self.rows[row] = 0;
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Iterates over the indices of the damaged tiles, in order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let damaged_rows =
+            (0 .. self.rows.len()).filter(move |&row| self.rows[row] > 0);
+        damaged_rows.flat_map(move |row| {
+            let words = self.row_range(row);
+            self.bits[words].iter().enumerate().flat_map(move |(i, &word)| {
+                let start = row * self.width + i * WORD_BITS;
+                let mut word = word;
+                iter::from_fn(move || {
+                    if word == 0 {
+                        None
+                    } else {
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        Some(start + bit)
+                    }
+                })
+            })
+        })
+    }
+
+    /// Range of the bitmap words of the given row.
+    fn row_range(&self, row: usize) -> Range<usize> {
+        row * self.row_words .. (row + 1) * self.row_words
+    }
+
+    /// Finds the bitmap word and the bit mask of the tile at the given index.
+    fn locate(&self, index: usize) -> (usize, u64) {
+        let (row, column) = (index / self.width, index % self.width);
+        let word = row * self.row_words + column / WORD_BITS;
+        (word, 1 << (column % WORD_BITS))
+    }
+}
@@ -0,0 +1,402 @@
+//! This module defines how the changed tiles of a screen are written to the
+//! terminal, using as few bytes as possible: the cheapest cursor movement is
+//! chosen, and trailing blank tiles may be erased at once and repeated
+//! graphemes repeated by the terminal.
+
+use crate::{
+    color::{Color2, ColorSupport},
+    coord::{self, Coord, Vec2},
+    screen::buffer::ScreenBuffer,
+    tile::{Attributes, Link, Tile},
+};
+use std::fmt::{self, Write};
+
+/// Estimated cost in bytes of a relative cursor movement, used to compare
+/// tiles drawn one by one with erasing or repeating them at once.
+const MOVE_COST: usize = 4;
+
+/// Erases from the cursor to the end of the line (`EL`).
+const ERASE_LINE: &str = "\x1b[K";
+
+/// Style set in the terminal, which the next graphemes are written with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pen {
+    /// The foreground-background pair of colors.
+    colors: Color2,
+    /// Text attributes such as bold or underline.
+    attributes: Attributes,
+    /// Hyperlink target, if any.
+    link: Option<Link>,
+}
+
+impl Pen {
+    /// The style the given tile is shown with, given the colors supported.
+    fn of(tile: &Tile, color_support: ColorSupport) -> Self {
+        let mut attributes = tile.attributes;
+        attributes.insert(color_support.extra_attributes(tile.colors));
+        let colors = match color_support {
+            // Every color is shown as the default one.
+            ColorSupport::NoColor => Color2::default(),
+            _ => tile.colors,
+        };
+        Self { colors, attributes, link: tile.link.clone() }
+    }
+}
+
+/// A frame being written from a screen buffer into a string.
+#[derive(Debug)]
+pub struct Frame<'buffer> {
+    /// The buffer being rendered.
+    buffer: &'buffer ScreenBuffer,
+    /// Colors supported by the terminal.
+    color_support: ColorSupport,
+    /// Whether rows are moved relatively, since the position of the screen in
+    /// the terminal is not known (inline rendering).
+    inline: bool,
+    /// Whether trailing blank tiles are erased with `EL`.
+    erase: bool,
+    /// Whether repeated graphemes are written with `REP`.
+    repeat: bool,
+    /// Where the terminal's cursor is, relative to the screen. A column equal
+    /// to the width means the cursor is past the last column, where relative
+    /// horizontal movement is not reliable.
+    cursor: Vec2,
+    /// Style set in the terminal, unknown until the first tile is drawn.
+    pen: Option<Pen>,
+    /// Start of the last row visited and of its trailing blank tiles.
+    blanks: Option<(usize, usize)>,
+}
+
+impl<'buffer> Frame<'buffer> {
+    /// Starts a frame of the given buffer, with the terminal's cursor where
+    /// the last output left it. Without colors, the background is always the
+    /// terminal's default one, so blank tiles are erased even if not asked to.
+    pub fn new(
+        buffer: &'buffer ScreenBuffer,
+        color_support: ColorSupport,
+        inline: bool,
+        erase: bool,
+        repeat: bool,
+    ) -> Self {
+        Self {
+            buffer,
+            color_support,
+            inline,
+            erase: erase || color_support == ColorSupport::NoColor,
+            repeat,
+            cursor: buffer.cursor,
+            pen: None,
+            blanks: None,
+        }
+    }
+
+    /// Where the terminal's cursor is, relative to the screen.
+    pub fn cursor(&self) -> Vec2 {
+        self.cursor
+    }
+
+    /// Draws every changed tile. Links are closed at the end, so that they do
+    /// not leak out of the frame.
+    pub fn draw(&mut self, buf: &mut String) -> fmt::Result {
+        let buffer = self.buffer;
+        let mut next = 0;
+        for index in buffer.changed.iter() {
+            if index < next || buffer.is_continuation(index) {
+                // Either already drawn along with the previous tiles, or by
+                // the wide grapheme at its left.
+                continue;
+            }
+            next = self.draw_from(buf, index)?;
+        }
+
+        if let Some(pen) = &mut self.pen {
+            if pen.link.is_some() {
+                Link::write_change(None, buf)?;
+                pen.link = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the terminal's cursor to the given point with the cheapest
+    /// movement: absolute, relative, after a carriage return, or overwriting
+    /// the tiles in between with what they already show.
+    pub fn move_to(&mut self, buf: &mut String, to: Vec2) -> fmt::Result {
+        let from = self.cursor;
+        if from == to {
+            return Ok(());
+        }
+        let width = self.buffer.size().x;
+        let column = Some(from.x).filter(|&x| x < width);
+
+        let mut best = String::new();
+        if !self.inline {
+            write!(best, "\x1b[{};{}H", to.y + 1, to.x + 1)?;
+        }
+
+        let mut candidate = String::new();
+        if to.y > from.y {
+            write_csi(&mut candidate, to.y - from.y, 'B')?;
+        } else if to.y < from.y {
+            write_csi(&mut candidate, from.y - to.y, 'A')?;
+        }
+        self.write_horizontal(&mut candidate, column, to)?;
+        keep_shortest(&mut best, &mut candidate);
+
+        candidate.push('\r');
+        if to.y > from.y {
+            let rows = to.y - from.y;
+            let mut down = String::new();
+            write_csi(&mut down, rows, 'B')?;
+            if usize::from(rows) < down.len() {
+                (0 .. rows).for_each(|_| candidate.push('\n'));
+            } else {
+                candidate.push_str(&down);
+            }
+        } else if to.y < from.y {
+            write_csi(&mut candidate, from.y - to.y, 'A')?;
+        }
+        self.write_horizontal(&mut candidate, Some(0), to)?;
+        keep_shortest(&mut best, &mut candidate);
+
+        buf.push_str(&best);
+        self.cursor = to;
+        Ok(())
+    }
+
+    /// Draws the changed tile at the given index, and possibly the tiles after
+    /// it in the same row, if cheaper at once. Returns the index of the first
+    /// tile not drawn.
+    fn draw_from(
+        &mut self,
+        buf: &mut String,
+        index: usize,
+    ) -> Result<usize, fmt::Error> {
+        let buffer = self.buffer;
+        let coord = buffer.make_point(index);
+        let row_start = index - coord::to_index(coord.x);
+        let row_end = row_start + coord::to_index(buffer.size().x);
+        let tile = &buffer.curr[index];
+
+        if self.erase
+            && index >= self.blank_start(row_start, row_end)
+            && ERASE_LINE.len() < self.plain_cost(index, row_end)
+        {
+            self.move_to(buf, coord)?;
+            self.set_pen(buf, tile)?;
+            buf.push_str(ERASE_LINE);
+            return Ok(row_end);
+        }
+
+        self.move_to(buf, coord)?;
+        self.set_pen(buf, tile)?;
+        write!(buf, "{}", tile.grapheme)?;
+        self.cursor.x += coord::from_index(tile.grapheme.width());
+
+        let single_char = tile.grapheme.chars().nth(1).is_none();
+        if !self.repeat || !single_char || tile.grapheme.width() != 1 {
+            return Ok(index + 1);
+        }
+        let count = buffer.curr[index + 1 .. row_end]
+            .iter()
+            .take_while(|other| *other == tile)
+            .count();
+        let end = index + 1 + count;
+        let mut repeat = String::new();
+        write_csi(&mut repeat, coord::from_index(count), 'b')?;
+        if count > 0 && repeat.len() < self.plain_cost(index + 1, end) {
+            buf.push_str(&repeat);
+            self.cursor.x += coord::from_index(count);
+            Ok(end)
+        } else {
+            Ok(index + 1)
+        }
+    }
+
+    /// Start of the trailing run of blank spaces with the same background in
+    /// the row with the given range, which can be erased at once. Computed
+    /// once per row.
+    fn blank_start(&mut self, row_start: usize, row_end: usize) -> usize {
+        if let Some((row, start)) = self.blanks {
+            if row == row_start {
+                return start;
+            }
+        }
+        let row = &self.buffer.curr[row_start .. row_end];
+        let support = self.color_support;
+        let background =
+            row.last().map(|tile| Pen::of(tile, support).colors.background);
+        let count = row
+            .iter()
+            .rev()
+            .take_while(|tile| {
+                let pen = Pen::of(tile, support);
+                tile.grapheme.as_str() == " "
+                    && Some(pen.colors.background) == background
+                    && pen.attributes == Attributes::NONE
+                    && pen.link.is_none()
+            })
+            .count();
+        let start = row_end - count;
+        self.blanks = Some((row_start, start));
+        start
+    }
+
+    /// Estimated cost in bytes of drawing the changed tiles in the given range
+    /// one by one, skipping the unchanged ones.
+    fn plain_cost(&self, start: usize, end: usize) -> usize {
+        let mut cost = 0;
+        let mut gap = 0;
+        for index in start .. end {
+            let tile = &self.buffer.curr[index];
+            let len = if self.buffer.is_continuation(index) {
+                0
+            } else {
+                tile.grapheme.len()
+            };
+            if self.buffer.changed.contains(index) {
+                cost += gap.min(MOVE_COST) + len;
+                gap = 0;
+            } else {
+                gap += len;
+            }
+        }
+        cost
+    }
+
+    /// Writes the cheapest horizontal movement to the given point's column,
+    /// from the given column in its row, or from an unknown column if `None`.
+    fn write_horizontal(
+        &self,
+        buf: &mut String,
+        from: Option<Coord>,
+        to: Vec2,
+    ) -> fmt::Result {
+        let mut best = String::new();
+        write!(best, "\x1b[{}G", to.x + 1)?;
+
+        if let Some(from) = from {
+            if from == to.x {
+                return Ok(());
+            }
+            let mut candidate = String::new();
+            if to.x > from {
+                write_csi(&mut candidate, to.x - from, 'C')?;
+            } else {
+                write_csi(&mut candidate, from - to.x, 'D')?;
+            }
+            keep_shortest(&mut best, &mut candidate);
+
+            if to.x > from && self.gap_cheaper(from, to, best.len()) {
+                self.write_gap(&mut best, from, to)?;
+            }
+        }
+
+        buf.push_str(&best);
+        Ok(())
+    }
+
+    /// Whether moving right to the given point by writing the tiles before it
+    /// in its row, starting at the given column, is possible with the current
+    /// style and costs less than the given number of bytes.
+    fn gap_cheaper(&self, from: Coord, to: Vec2, limit: usize) -> bool {
+        let (pen, start, end) = match (
+            &self.pen,
+            self.buffer.make_index(Vec2 { x: from, y: to.y }),
+            self.buffer.make_index(to),
+        ) {
+            (Some(pen), Some(start), Some(end)) => (pen, start, end),
+            _ => return false,
+        };
+        if self.buffer.is_continuation(start)
+            || self.buffer.is_continuation(end)
+        {
+            // Would write half of a wide grapheme.
+            return false;
+        }
+        let mut cost = 0;
+        for index in start .. end {
+            let tile = &self.buffer.curr[index];
+            if !self.buffer.is_continuation(index) {
+                cost += tile.grapheme.len();
+                if cost >= limit || Pen::of(tile, self.color_support) != *pen {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Moves right by writing the tiles before the given point in its row,
+    /// which must be possible according to [`Frame::gap_cheaper`].
+    fn write_gap(
+        &self,
+        buf: &mut String,
+        from: Coord,
+        to: Vec2,
+    ) -> fmt::Result {
+        buf.clear();
+        for x in from .. to.x {
+            let index = self.buffer.make_index(Vec2 { x, y: to.y });
+            if let Some(index) = index {
+                if !self.buffer.is_continuation(index) {
+                    write!(buf, "{}", self.buffer.curr[index].grapheme)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the terminal's style to the given tile's, writing only what
+    /// differs, or everything if the current style is not known.
+    fn set_pen(&mut self, buf: &mut String, tile: &Tile) -> fmt::Result {
+        let target = Pen::of(tile, self.color_support);
+        let (colors, attributes, link) = match &self.pen {
+            Some(pen) => (Some(pen.colors), pen.attributes, pen.link.clone()),
+            None => {
+                let reset = crossterm::style::Attribute::Reset;
+                write!(buf, "{}", crossterm::style::SetAttribute(reset))?;
+                (None, Attributes::NONE, None)
+            },
+        };
+
+        let support = self.color_support;
+        let background = colors.map(|colors| colors.background);
+        if background != Some(target.colors.background) {
+            let color = support.to_crossterm(target.colors.background);
+            write!(buf, "{}", crossterm::style::SetBackgroundColor(color))?;
+        }
+        let foreground = colors.map(|colors| colors.foreground);
+        if foreground != Some(target.colors.foreground) {
+            let color = support.to_crossterm(target.colors.foreground);
+            write!(buf, "{}", crossterm::style::SetForegroundColor(color))?;
+        }
+        target.attributes.write_diff(attributes, buf)?;
+        if link != target.link {
+            Link::write_change(target.link.as_ref(), buf)?;
+        }
+
+        self.pen = Some(target);
+        Ok(())
+    }
+}
+
+/// Writes a control sequence with a count parameter, omitted if 1 (the
+/// default for cursor movement and `REP`).
+fn write_csi(buf: &mut String, count: Coord, end: char) -> fmt::Result {
+    if count == 1 {
+        write!(buf, "\x1b[{}", end)
+    } else {
+        write!(buf, "\x1b[{}{}", count, end)
+    }
+}
+
+/// Keeps in `best` the shortest between it and the candidate, preferring
+/// `best` on ties, unless `best` is empty (i.e. no candidate so far). The
+/// candidate is cleared.
+fn keep_shortest(best: &mut String, candidate: &mut String) {
+    if best.is_empty() || candidate.len() < best.len() {
+        std::mem::swap(best, candidate);
+    }
+    candidate.clear();
+}
@@ -0,0 +1,258 @@
+//! This module defines a snapshot of the screen.
+
+use crate::{
+    color::{Color, Color2},
+    coord,
+    coord::{Coord, Vec2},
+    tile::{Attributes, Tile},
+};
+use crossterm::style::{
+    Attribute as CrosstermAttr,
+    SetAttribute,
+    SetBackgroundColor,
+    SetForegroundColor,
+};
+use std::{
+    fmt::{self, Write},
+    io,
+};
+
+/// An owned copy of every [`Tile`] of the screen at some point in time. Can be
+/// obtained through [`Screen::snapshot`](crate::screen::Screen::snapshot).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    /// Size of the screen when the snapshot was taken.
+    size: Vec2,
+    /// Tiles in row-major order.
+    tiles: Vec<Tile>,
+    /// Where the cursor was shown, if anywhere.
+    cursor: Option<Vec2>,
+}
+
+impl Snapshot {
+    /// Creates a snapshot from the given size and tiles in row-major order.
+    pub(crate) fn new(size: Vec2, tiles: Vec<Tile>) -> Self {
+        Self { size, tiles, cursor: None }
+    }
+
+    /// Sets where the cursor was shown.
+    pub(crate) fn with_cursor(self, cursor: Option<Vec2>) -> Self {
+        Self { cursor, ..self }
+    }
+
+    /// Where the cursor was shown when the snapshot was taken, if anywhere.
+    /// See [`Screen::set_cursor`](crate::screen::Screen::set_cursor).
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.cursor
+    }
+
+    /// Size of the screen when the snapshot was taken.
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Gets the [`Tile`] at the given point, or `None` if out of bounds.
+    pub fn get(&self, point: Vec2) -> Option<&Tile> {
+        self.index(point).map(|index| &self.tiles[index])
+    }
+
+    /// Whether the given point is the continuation (right half) of a wide
+    /// grapheme, and therefore its tile is not shown.
+    pub fn is_continuation(&self, point: Vec2) -> bool {
+        let left = point.x.checked_sub(1).map(|x| Vec2 { x, ..point });
+        matches!(
+            left.and_then(|left| self.get(left)),
+            Some(tile) if tile.grapheme.width() > 1
+        )
+    }
+
+    /// Iterates over the tiles of the given row, paired with their
+    /// coordinates, skipping continuations of wide graphemes.
+    pub fn row(&self, y: Coord) -> impl Iterator<Item = (Vec2, &Tile)> {
+        let width = if y < self.size.y { self.size.x } else { 0 };
+        (0 .. width)
+            .map(move |x| Vec2 { x, y })
+            .filter(move |&point| !self.is_continuation(point))
+            .filter_map(move |point| self.get(point).map(|tile| (point, tile)))
+    }
+
+    /// The text shown in the given row, i.e. its graphemes concatenated.
+    pub fn row_text(&self, y: Coord) -> String {
+        self.row(y).map(|(_, tile)| tile.grapheme.as_str()).collect()
+    }
+
+    /// Exports the snapshot as plain text: the graphemes of every row, each
+    /// row terminated by a newline. Colors and attributes are discarded.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0 .. self.size.y {
+            text.push_str(&self.row_text(y));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Exports the snapshot as text with ANSI escape sequences for colors and
+    /// attributes, which a terminal (e.g. through `cat`) displays just like the
+    /// screen. Every row is terminated by a newline, with colors and
+    /// attributes reset before it.
+    pub fn to_ansi(&self) -> String {
+        let mut text = String::new();
+        for y in 0 .. self.size.y {
+            self.write_ansi_row(y, &mut text)
+                .expect("Writing to String failed");
+        }
+        text
+    }
+
+    /// Writes the ANSI export (see [`Snapshot::to_ansi`]) to the given writer,
+    /// one row at a time.
+    pub fn write_ansi<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let mut text = String::new();
+        for y in 0 .. self.size.y {
+            text.clear();
+            self.write_ansi_row(y, &mut text)
+                .expect("Writing to String failed");
+            writer.write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes a row of the ANSI export. The reset of the attributes resets the
+    /// colors too.
+    fn write_ansi_row(&self, y: Coord, text: &mut String) -> fmt::Result {
+        let mut colors = None;
+        let mut attributes = Attributes::NONE;
+        for (_, tile) in self.row(y) {
+            if colors != Some(tile.colors) {
+                let fg = tile.colors.foreground.to_crossterm();
+                let bg = tile.colors.background.to_crossterm();
+                write!(
+                    text,
+                    "{}{}",
+                    SetForegroundColor(fg),
+                    SetBackgroundColor(bg)
+                )?;
+                colors = Some(tile.colors);
+            }
+            tile.attributes.write_diff(attributes, text)?;
+            attributes = tile.attributes;
+            text.push_str(tile.grapheme.as_str());
+        }
+        writeln!(text, "{}", SetAttribute(CrosstermAttr::Reset))
+    }
+
+    /// Exports the snapshot as a standalone HTML page, with colors and
+    /// attributes as inline styles. Basic and 8-bit colors are converted with
+    /// xterm's default palette (see [`Color::to_rgb`]).
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        self.write_html(&mut html).expect("Writing to String failed");
+        html
+    }
+
+    /// Writes the HTML export (see [`Snapshot::to_html`]).
+    fn write_html(&self, html: &mut String) -> fmt::Result {
+        let background = css_color(Color2::default().background);
+        writeln!(html, "<!DOCTYPE html>")?;
+        writeln!(html, "<html>")?;
+        writeln!(html, "<head>")?;
+        writeln!(html, "<meta charset=\"utf-8\">")?;
+        writeln!(html, "<title>Screen snapshot</title>")?;
+        writeln!(html, "</head>")?;
+        writeln!(html, "<body style=\"background-color: {};\">", background)?;
+        write!(html, "<pre style=\"font-family: monospace;\">")?;
+
+        for y in 0 .. self.size.y {
+            let mut style = None;
+            for (_, tile) in self.row(y) {
+                if style != Some((tile.colors, tile.attributes)) {
+                    if style.is_some() {
+                        html.push_str("</span>");
+                    }
+                    write_html_style(html, tile.colors, tile.attributes)?;
+                    style = Some((tile.colors, tile.attributes));
+                }
+                for ch in tile.grapheme.as_str().chars() {
+                    match ch {
+                        '&' => html.push_str("&amp;"),
+                        '<' => html.push_str("&lt;"),
+                        '>' => html.push_str("&gt;"),
+                        '"' => html.push_str("&quot;"),
+                        _ => html.push(ch),
+                    }
+                }
+            }
+            if style.is_some() {
+                html.push_str("</span>");
+            }
+            html.push('\n');
+        }
+
+        writeln!(html, "</pre>")?;
+        writeln!(html, "</body>")?;
+        writeln!(html, "</html>")?;
+        Ok(())
+    }
+
+    /// Makes an index from a coordinate.
+    fn index(&self, point: Vec2) -> Option<usize> {
+        if point.x < self.size.x && point.y < self.size.y {
+            let x = coord::to_index(point.x);
+            let y = coord::to_index(point.y);
+            Some(y * coord::to_index(self.size.x) + x)
+        } else {
+            None
+        }
+    }
+}
+
+/// Formats a color as a CSS hexadecimal color.
+fn css_color(color: Color) -> String {
+    let rgb = color.to_rgb();
+    format!("#{:02x}{:02x}{:02x}", rgb.red, rgb.green, rgb.blue)
+}
+
+/// Opens an HTML `span` styled with the given colors and attributes.
+fn write_html_style(
+    html: &mut String,
+    colors: Color2,
+    attributes: Attributes,
+) -> fmt::Result {
+    let (foreground, background) = if attributes.contains(Attributes::REVERSE) {
+        (colors.background, colors.foreground)
+    } else {
+        (colors.foreground, colors.background)
+    };
+
+    write!(
+        html,
+        "<span style=\"color: {}; background-color: {};",
+        css_color(foreground),
+        css_color(background)
+    )?;
+    if attributes.contains(Attributes::BOLD) {
+        html.push_str(" font-weight: bold;");
+    }
+    if attributes.contains(Attributes::DIM) {
+        html.push_str(" opacity: 0.5;");
+    }
+    if attributes.contains(Attributes::ITALIC) {
+        html.push_str(" font-style: italic;");
+    }
+    let underline = attributes.contains(Attributes::UNDERLINE);
+    let strikethrough = attributes.contains(Attributes::STRIKETHROUGH);
+    match (underline, strikethrough) {
+        (true, true) => {
+            html.push_str(" text-decoration: underline line-through;")
+        },
+        (true, false) => html.push_str(" text-decoration: underline;"),
+        (false, true) => html.push_str(" text-decoration: line-through;"),
+        (false, false) => (),
+    }
+    html.push_str("\">");
+    Ok(())
+}
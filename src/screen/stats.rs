@@ -0,0 +1,127 @@
+//! This module defines statistics of the frames rendered to the terminal.
+
+use std::{
+    fmt,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Statistics of a single frame, given to
+/// [`Builder::on_frame`](crate::terminal::Builder::on_frame).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FrameStats {
+    /// Time spent going through the changed tiles and producing the output.
+    pub diff_time: Duration,
+    /// Bytes written to the terminal (zero if dropped).
+    pub bytes: usize,
+    /// Number of tiles changed since the previous frame.
+    pub changed_tiles: usize,
+    /// Time the application held the terminal locked (e.g. through
+    /// [`Terminal::lock_now`](crate::terminal::Terminal::lock_now)) since the
+    /// previous frame.
+    pub app_lock_time: Duration,
+    /// Whether the frame was dropped because the output was locked by someone
+    /// else. The changes are kept for the next frame.
+    pub dropped: bool,
+}
+
+/// Statistics accumulated over every frame since the terminal started, given
+/// by [`Terminal::stats`](crate::terminal::Terminal::stats).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Stats {
+    /// Number of frames, including dropped ones.
+    pub frames: u64,
+    /// Number of frames dropped because the output was locked.
+    pub dropped_frames: u64,
+    /// Total bytes written to the terminal by frames.
+    pub bytes: u64,
+    /// Total number of changed tiles.
+    pub changed_tiles: u64,
+    /// Total time spent going through the changed tiles and producing the
+    /// output.
+    pub diff_time: Duration,
+    /// Total time the application held the terminal locked, up to the last
+    /// frame.
+    pub app_lock_time: Duration,
+    /// Statistics of the last frame, if any.
+    pub last_frame: Option<FrameStats>,
+}
+
+impl Stats {
+    /// Accumulates the statistics of a frame.
+    fn add(&mut self, frame: FrameStats) {
+        self.frames += 1;
+        if frame.dropped {
+            self.dropped_frames += 1;
+        }
+        self.bytes += frame.bytes as u64;
+        self.changed_tiles += frame.changed_tiles as u64;
+        self.diff_time += frame.diff_time;
+        self.app_lock_time += frame.app_lock_time;
+        self.last_frame = Some(frame);
+    }
+}
+
+/// A function called with the statistics of every frame.
+pub(crate) struct FrameHook {
+    /// The function itself.
+    callback: Box<dyn Fn(&FrameStats) + Send + Sync>,
+}
+
+impl FrameHook {
+    /// Creates a hook from the given function.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&FrameStats) + Send + Sync + 'static,
+    {
+        Self { callback: Box::new(callback) }
+    }
+}
+
+impl fmt::Debug for FrameHook {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("FrameHook").finish_non_exhaustive()
+    }
+}
+
+/// Collects the statistics of the frames, and of how long the application
+/// held the terminal locked between them.
+#[derive(Debug, Default)]
+pub(crate) struct StatsCollector {
+    /// Statistics accumulated so far.
+    totals: Mutex<Stats>,
+    /// Time the application held the terminal locked since the last frame.
+    app_lock_time: Mutex<Duration>,
+    /// Function called with the statistics of every frame, if any.
+    hook: Option<Arc<FrameHook>>,
+}
+
+impl StatsCollector {
+    /// Creates a collector which calls the given hook on every frame, if any.
+    pub fn new(hook: Option<Arc<FrameHook>>) -> Self {
+        Self { hook, ..Self::default() }
+    }
+
+    /// Records that the application released the terminal after holding it
+    /// for the given time.
+    pub fn app_unlocked(&self, held: Duration) {
+        *self.app_lock_time.lock().unwrap() += held;
+    }
+
+    /// Records a frame and calls the hook. The application's lock time is
+    /// filled in here.
+    pub fn frame(&self, mut frame: FrameStats) {
+        frame.app_lock_time =
+            mem::take(&mut *self.app_lock_time.lock().unwrap());
+        self.totals.lock().unwrap().add(frame);
+        if let Some(hook) = &self.hook {
+            (hook.callback)(&frame);
+        }
+    }
+
+    /// Statistics accumulated so far.
+    pub fn totals(&self) -> Stats {
+        *self.totals.lock().unwrap()
+    }
+}
@@ -0,0 +1,396 @@
+use crate::{
+    color::{BasicColor, Color2, ColorSupport},
+    coord::Vec2,
+    screen::{
+        asciicast,
+        buffer::ScreenBuffer,
+        damage::Damage,
+        render::Frame,
+        Recorder,
+        Snapshot,
+    },
+    string::{TermGrapheme, TermString},
+    testing::SharedWriter,
+    tile::{Attributes, Tile},
+};
+
+fn grapheme(string: &str) -> TermGrapheme {
+    TermGrapheme::new(string).unwrap()
+}
+
+fn put(buffer: &mut ScreenBuffer, x: u16, string: &str) {
+    let index = buffer.make_index(Vec2 { x, y: 0 }).unwrap();
+    let grapheme = grapheme(string);
+    buffer.update(index, |tile: &mut Tile| tile.grapheme = grapheme);
+}
+
+fn row(buffer: &ScreenBuffer) -> String {
+    let mut string = String::new();
+    for (index, tile) in buffer.curr.iter().enumerate() {
+        if buffer.is_continuation(index) {
+            string.push('|');
+        } else {
+            string.push_str(tile.grapheme.as_str());
+        }
+    }
+    string
+}
+
+#[test]
+fn wide_takes_continuation() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 4, y: 1 });
+    put(&mut buffer, 1, "漢");
+    assert_eq!(row(&buffer), " 漢| ");
+    assert!(buffer.changed.contains(1));
+    assert!(buffer.changed.contains(2));
+}
+
+#[test]
+fn overwriting_half_breaks_wide() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 4, y: 1 });
+    put(&mut buffer, 1, "漢");
+    put(&mut buffer, 2, "a");
+    assert_eq!(row(&buffer), "  a ");
+
+    put(&mut buffer, 1, "漢");
+    put(&mut buffer, 1, "b");
+    assert_eq!(row(&buffer), " b  ");
+}
+
+#[test]
+fn wide_over_wide() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 5, y: 1 });
+    put(&mut buffer, 2, "漢");
+    put(&mut buffer, 1, "字");
+    assert_eq!(row(&buffer), " 字|  ");
+}
+
+#[test]
+fn wide_does_not_fit_last_column() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 3, y: 1 });
+    put(&mut buffer, 2, "漢");
+    assert_eq!(row(&buffer), "   ");
+}
+
+#[test]
+fn unchanged_wide_is_not_marked() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 4, y: 1 });
+    put(&mut buffer, 0, "漢");
+    buffer.next_tick();
+    put(&mut buffer, 0, "漢");
+    assert!(buffer.changed.is_empty());
+    put(&mut buffer, 0, "a");
+    assert!(buffer.changed.contains(1));
+}
+
+#[test]
+fn next_tick_syncs_buffers() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 4, y: 2 });
+    put(&mut buffer, 1, "漢");
+    buffer.next_tick();
+    assert!(buffer.changed.is_empty());
+    assert!(buffer.old == buffer.curr);
+    put(&mut buffer, 0, "a");
+    put(&mut buffer, 2, "b");
+    assert_eq!(buffer.changed.iter().collect::<Vec<_>>(), [0, 1, 2]);
+    buffer.next_tick();
+    assert!(buffer.old == buffer.curr);
+    assert_eq!(row(&buffer), "a b     ");
+}
+
+#[test]
+fn damage_rows() {
+    let mut damage = Damage::new(70, 140);
+    for &index in &[135, 3, 69, 3] {
+        damage.insert(index);
+    }
+    assert_eq!(damage.len(), 3);
+    assert_eq!(damage.iter().collect::<Vec<_>>(), [3, 69, 135]);
+    damage.remove(69);
+    damage.remove(68);
+    assert!(!damage.contains(69));
+    assert_eq!(damage.iter().collect::<Vec<_>>(), [3, 135]);
+
+    damage.fill();
+    assert_eq!(damage.len(), 140);
+    assert!(damage.iter().eq(0 .. 140));
+    damage.clear();
+    assert!(damage.is_empty());
+    assert_eq!(damage.iter().next(), None);
+
+    damage.resize(3, 6);
+    damage.insert(5);
+    assert_eq!(damage.iter().collect::<Vec<_>>(), [5]);
+}
+
+/// Plays frames on a grid of graphemes as a terminal would, understanding
+/// only the sequences written by the renderer. Styles are ignored.
+struct Emulator {
+    width: usize,
+    rows: Vec<Vec<String>>,
+    cursor: (usize, usize),
+    last: String,
+}
+
+impl Emulator {
+    fn new(size: Vec2) -> Self {
+        let row = vec![" ".to_owned(); usize::from(size.x)];
+        Self {
+            width: usize::from(size.x),
+            rows: vec![row; usize::from(size.y)],
+            cursor: (0, 0),
+            last: String::new(),
+        }
+    }
+
+    fn print(&mut self, grapheme: &str, width: usize) {
+        let (x, y) = self.cursor;
+        self.rows[y][x.min(self.width - 1)] = grapheme.to_owned();
+        if width > 1 {
+            self.rows[y][x + 1] = "|".to_owned();
+        }
+        self.cursor.0 = (x + width).min(self.width);
+        self.last = grapheme.to_owned();
+    }
+
+    fn play(&mut self, output: &str) {
+        let mut chars = output.chars().peekable();
+        let mut text = String::new();
+        while let Some(ch) = chars.next() {
+            if !ch.is_control() {
+                text.push(ch);
+                if chars.peek().is_some_and(|next| !next.is_control()) {
+                    continue;
+                }
+                for grapheme in TermString::new_lossy(&text).iter() {
+                    self.print(grapheme.as_str(), grapheme.width());
+                }
+                text.clear();
+            } else if ch == '\r' {
+                self.cursor.0 = 0;
+            } else if ch == '\n' {
+                self.cursor.1 += 1;
+            } else if chars.next() == Some(']') {
+                // Links are ignored.
+                while chars.next() != Some('\\') {}
+            } else {
+                let mut params = String::new();
+                let end = loop {
+                    match chars.next().unwrap() {
+                        ch @ '@' ..= '~' => break ch,
+                        ch => params.push(ch),
+                    }
+                };
+                let count =
+                    params.split(';').next().unwrap().parse().unwrap_or(1);
+                let (x, y) = self.cursor;
+                let x = x.min(self.width - 1);
+                match end {
+                    'A' => self.cursor.1 -= count,
+                    'B' => self.cursor.1 += count,
+                    'C' => self.cursor.0 = x + count,
+                    'D' => self.cursor.0 = x - count,
+                    'G' => self.cursor.0 = count - 1,
+                    'H' => {
+                        let column = params.split(';').nth(1).unwrap();
+                        self.cursor =
+                            (column.parse::<usize>().unwrap() - 1, count - 1);
+                    },
+                    'K' => {
+                        for tile in &mut self.rows[y][x ..] {
+                            *tile = " ".to_owned();
+                        }
+                    },
+                    'b' => {
+                        let last = self.last.clone();
+                        (0 .. count).for_each(|_| self.print(&last, 1));
+                    },
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn text(&self) -> String {
+        self.rows.concat().concat()
+    }
+}
+
+fn set_row(buffer: &mut ScreenBuffer, y: u16, graphemes: &[&str]) {
+    for (x, string) in graphemes.iter().enumerate() {
+        if let Some(index) = buffer.make_index(Vec2 { x: x as u16, y }) {
+            let grapheme = grapheme(string);
+            buffer.update(index, |tile: &mut Tile| tile.grapheme = grapheme);
+        }
+    }
+}
+
+fn render_frame(
+    buffer: &mut ScreenBuffer,
+    inline: bool,
+    optimize: bool,
+) -> String {
+    let mut output = String::new();
+    let mut frame =
+        Frame::new(buffer, ColorSupport::Rgb, inline, optimize, optimize);
+    frame.draw(&mut output).unwrap();
+    let cursor = frame.cursor();
+    buffer.cursor = cursor;
+    buffer.next_tick();
+    output
+}
+
+#[test]
+fn frames_reproduce_buffer() {
+    let size = Vec2 { x: 12, y: 4 };
+    let frames: &[&[(u16, &[&str])]] = &[
+        &[(0, &["a", "b", "c"]), (2, &["x"; 12])],
+        &[(0, &[" "; 12]), (1, &["漢", "字", "-", "-", "-", "-", "k"])],
+        &[(1, &[" ", "y", " ", " ", " ", "-", "-", "-"]), (3, &["z"; 9])],
+        &[(2, &["x", " ", "x", " ", "q", "q", "q", "q", " ", " ", " "])],
+        &[(3, &["z", "z", "z", " ", " ", " ", " ", " ", " ", " ", " ", "!"])],
+    ];
+    for &(inline, optimize) in
+        &[(false, false), (false, true), (true, false), (true, true)]
+    {
+        let mut buffer = ScreenBuffer::blank(size);
+        let mut emulator = Emulator::new(size);
+        for frame in frames {
+            for &(y, graphemes) in frame.iter() {
+                set_row(&mut buffer, y, graphemes);
+            }
+            emulator.play(&render_frame(&mut buffer, inline, optimize));
+            assert_eq!(emulator.text(), row(&buffer));
+            assert_eq!(buffer.cursor.x as usize, emulator.cursor.0);
+            assert_eq!(buffer.cursor.y as usize, emulator.cursor.1);
+        }
+    }
+}
+
+#[test]
+fn frame_erases_and_repeats() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 20, y: 3 });
+    set_row(&mut buffer, 1, &["-"; 20]);
+    let output = render_frame(&mut buffer, false, true);
+    assert!(output.ends_with("-\x1b[19b"));
+
+    set_row(&mut buffer, 1, &["a", "b"]);
+    set_row(&mut buffer, 1, &[" "; 20][.. 2]);
+    let output = render_frame(&mut buffer, false, true);
+    assert!(!output.contains("\x1b[K"));
+    set_row(&mut buffer, 1, &[" "; 20]);
+    let output = render_frame(&mut buffer, false, true);
+    assert_eq!(output, "\x1b[0m\x1b[48;5;0m\x1b[38;5;15m\x1b[K");
+
+    set_row(&mut buffer, 2, &["a", "b"]);
+    let output = render_frame(&mut buffer, false, false);
+    assert!(output.starts_with("\r\n\x1b[0m"));
+    assert!(output.ends_with("ab"));
+}
+
+#[test]
+fn frame_erases_only_if_asked() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 20, y: 1 });
+    set_row(&mut buffer, 0, &["-"; 20]);
+    render_frame(&mut buffer, false, false);
+    set_row(&mut buffer, 0, &[" "; 20]);
+    let output = render_frame(&mut buffer, false, false);
+    assert!(!output.contains("\x1b[K"));
+    assert!(output.ends_with(&" ".repeat(20)));
+}
+
+#[test]
+fn inverted_colors_reversed_without_color() {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 4, y: 1 });
+    set_row(&mut buffer, 0, &["a", "b"]);
+    let index = buffer.make_index(Vec2 { x: 1, y: 0 }).unwrap();
+    buffer.update(index, |tile: &mut Tile| tile.colors = !tile.colors);
+    let mut output = String::new();
+    let mut frame =
+        Frame::new(&buffer, ColorSupport::NoColor, false, false, false);
+    frame.draw(&mut output).unwrap();
+    assert_eq!(output, "\x1b[0m\x1b[49m\x1b[39ma\x1b[7mb");
+}
+
+fn export_snapshot() -> Snapshot {
+    let mut buffer = ScreenBuffer::blank(Vec2 { x: 4, y: 2 });
+    put(&mut buffer, 0, "<");
+    put(&mut buffer, 1, "漢");
+    let index = buffer.make_index(Vec2 { x: 0, y: 1 }).unwrap();
+    buffer.update(index, |tile: &mut Tile| {
+        tile.grapheme = grapheme("&");
+        tile.colors = Color2::new(BasicColor::LightRed, BasicColor::DarkBlue);
+        tile.attributes = Attributes::BOLD;
+    });
+    Snapshot::new(buffer.size(), buffer.curr.clone())
+}
+
+#[test]
+fn export_text() {
+    assert_eq!(export_snapshot().to_text(), "<漢 \n&   \n");
+}
+
+#[test]
+fn export_ansi() {
+    let ansi = export_snapshot().to_ansi();
+    let lines = ansi.lines().collect::<Vec<_>>();
+    let second = [
+        "\x1b[38;5;9m\x1b[48;5;4m\x1b[1m&",
+        "\x1b[38;5;15m\x1b[48;5;0m\x1b[22m   ",
+        "\x1b[0m",
+    ]
+    .concat();
+    assert_eq!(
+        lines,
+        vec!["\x1b[38;5;15m\x1b[48;5;0m<漢 \x1b[0m", second.as_str()]
+    );
+}
+
+#[test]
+fn export_ansi_to_writer() {
+    let snapshot = export_snapshot();
+    let mut output = Vec::new();
+    snapshot.write_ansi(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), snapshot.to_ansi());
+}
+
+#[test]
+fn export_html() {
+    let html = export_snapshot().to_html();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(
+        "<span style=\"color: #ffffff; background-color: #000000;\">&lt;漢 \
+         </span>\n"
+    ));
+    assert!(html.contains(
+        "<span style=\"color: #ff0000; background-color: #0000ee; \
+         font-weight: bold;\">&amp;</span>"
+    ));
+}
+
+#[test]
+fn asciicast_escape() {
+    let mut escaped = Vec::new();
+    asciicast::write_escaped(&mut escaped, "a\"b\\c\x1b[0m\n漢").unwrap();
+    assert_eq!(escaped, "a\\\"b\\\\c\\u001b[0m\\n漢".as_bytes());
+}
+
+#[test]
+fn asciicast_recording() {
+    let writer = SharedWriter::default();
+    let recorder = Recorder::new(writer.clone());
+    recorder.start(Vec2 { x: 80, y: 24 });
+    recorder.output("hi\x1b[0m");
+    recorder.resize(Vec2 { x: 100, y: 30 });
+    recorder.finish();
+
+    let recording = writer.text();
+    let lines = recording.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(
+        lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24")
+    );
+    assert!(lines[1].starts_with('['));
+    assert!(lines[1].ends_with(", \"o\", \"hi\\u001b[0m\"]"));
+    assert!(lines[2].ends_with(", \"r\", \"100x30\"]"));
+}
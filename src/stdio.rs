@@ -1,32 +1,33 @@
 //! This module exports utilities related to terminal's standard input and
 //! output at a raw level.
 
+use crate::{backend::Output, terminal::NotifyProtocol};
 use crossterm::Command;
 use std::{
-    fmt,
+    fmt::{self, Write},
     pin::Pin,
     task::{Context, Poll},
 };
 use tokio::{
     io,
-    io::{AsyncWrite, AsyncWriteExt, Stdout as TokioStdout},
+    io::{AsyncWrite, AsyncWriteExt},
     sync::{Mutex, MutexGuard},
 };
 
-/// A centralized lock to a standard output handle.
-#[derive(Debug)]
+/// A centralized lock to the terminal's output handle (the standard output,
+/// with the default backend).
 pub struct Stdout {
-    /// A lock to tokio's stdout.
-    inner: Mutex<TokioStdout>,
+    /// A lock to the backend's output.
+    inner: Mutex<Output>,
 }
 
 impl Stdout {
-    /// Creates a new centralized stdout lock. You should NOT call this
-    /// function if there is already an active centralized lock to the Stdout.
-    /// You shouldn't also write to stdout through other means, such as
-    /// std's `println!`.
-    pub fn new() -> Stdout {
-        Self { inner: Mutex::new(io::stdout()) }
+    /// Creates a new centralized output lock from the backend's output. You
+    /// should NOT call this function if there is already an active centralized
+    /// lock to the same output. You shouldn't also write to the output through
+    /// other means, such as std's `println!`.
+    pub fn new(output: Output) -> Stdout {
+        Self { inner: Mutex::new(output) }
     }
 
     /// Locks this centralized lock to the standard output, and acquire a locked
@@ -47,11 +48,22 @@ impl Stdout {
     }
 }
 
+impl fmt::Debug for Stdout {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("Stdout").finish_non_exhaustive()
+    }
+}
+
 /// A locked standard output handle.
-#[derive(Debug)]
 pub struct LockedStdout<'stdout> {
     /// Guard of tokio's Mutex.
-    guard: MutexGuard<'stdout, TokioStdout>,
+    guard: MutexGuard<'stdout, Output>,
+}
+
+impl<'stdout> fmt::Debug for LockedStdout<'stdout> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("LockedStdout").finish_non_exhaustive()
+    }
 }
 
 impl<'stdout> LockedStdout<'stdout> {
@@ -89,7 +101,7 @@ impl<'stdout> AsyncWrite for LockedStdout<'stdout> {
     }
 }
 
-/// Saves the screen previous the application.
+/// Saves the screen from before the application.
 #[cfg(windows)]
 pub fn save_screen(buf: &mut String) -> fmt::Result {
     if crossterm::terminal::EnterAlternateScreen.is_ansi_code_supported() {
@@ -102,34 +114,102 @@ pub fn save_screen(buf: &mut String) -> fmt::Result {
     Ok(())
 }
 
-/// Saves the screen previous the application.
+/// Saves the screen from before the application.
 #[cfg(unix)]
 pub fn save_screen(buf: &mut String) -> fmt::Result {
     crossterm::terminal::EnterAlternateScreen.write_ansi(buf)?;
     Ok(())
 }
 
-/// Restores the screen previous the application.
+/// Restores the screen from before the application.
 #[cfg(windows)]
 pub fn restore_screen(buf: &mut String) -> fmt::Result {
+    let reset = crossterm::style::Attribute::Reset;
+    write!(buf, "{}", crossterm::style::SetAttribute(reset))?;
     if crossterm::terminal::LeaveAlternateScreen.is_ansi_code_supported() {
         crossterm::terminal::LeaveAlternateScreen.write_ansi(buf)?;
     }
     Ok(())
 }
 
-/// Restores the screen previous the application.
+/// Restores the screen from before the application.
 #[cfg(unix)]
 pub fn restore_screen(buf: &mut String) -> fmt::Result {
+    let reset = crossterm::style::Attribute::Reset;
+    write!(buf, "{}", crossterm::style::SetAttribute(reset))?;
     crossterm::terminal::LeaveAlternateScreen.write_ansi(buf)?;
     Ok(())
 }
 
+/// Saves the window title from before the application in the terminal's title
+/// stack.
+pub fn push_title(buf: &mut String) -> fmt::Result {
+    buf.write_str("\x1b[22;0t")
+}
+
+/// Restores the window title from before the application from the terminal's
+/// title stack.
+pub fn pop_title(buf: &mut String) -> fmt::Result {
+    buf.write_str("\x1b[23;0t")
+}
+
+/// Sets the window title. Control characters are removed from the title.
+pub fn set_title(buf: &mut String, title: &str) -> fmt::Result {
+    let title = sanitize(title, false);
+    crossterm::terminal::SetTitle(title).write_ansi(buf)
+}
+
+/// Emits a desktop notification with the given protocol. Control characters
+/// are removed from the title and the body.
+pub fn notification(
+    buf: &mut String,
+    protocol: NotifyProtocol,
+    title: &str,
+    body: &str,
+) -> fmt::Result {
+    match protocol {
+        NotifyProtocol::Osc9 => {
+            let message = if title.is_empty() {
+                sanitize(body, false)
+            } else {
+                format!("{}: {}", sanitize(title, false), sanitize(body, false))
+            };
+            write!(buf, "\x1b]9;{}\x1b\\", message)
+        },
+        NotifyProtocol::Osc777 => write!(
+            buf,
+            "\x1b]777;notify;{};{}\x1b\\",
+            sanitize(title, true),
+            sanitize(body, false)
+        ),
+    }
+}
+
+/// Removes control characters, which could end or break a sequence, and
+/// optionally semicolons, which separate parameters.
+fn sanitize(text: &str, semicolons: bool) -> String {
+    text.chars()
+        .filter(|&ch| !(ch.is_control() || semicolons && ch == ';'))
+        .collect()
+}
+
 #[cfg(windows)]
-/// Best-effort function to restore the terminal in a panic.
+/// Best-effort function to restore the terminal in a panic. Prefer
+/// [`Builder::panic_hook`](crate::terminal::Builder::panic_hook), which also
+/// knows the enabled input modes and the backend.
 pub fn emergency_restore() {
     let _ = crossterm::terminal::disable_raw_mode();
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::event::DisableMouseCapture,
+        crossterm::event::DisableBracketedPaste,
+        crossterm::event::DisableFocusChange
+    );
     print!("{}", crossterm::cursor::Show);
+    print!(
+        "{}",
+        crossterm::style::SetAttribute(crossterm::style::Attribute::Reset)
+    );
     print!(
         "{}",
         crossterm::style::SetBackgroundColor(crossterm::style::Color::Reset)
@@ -145,10 +225,22 @@ pub fn emergency_restore() {
 }
 
 #[cfg(unix)]
-/// Best-effort function to restore the terminal in a panic.
+/// Best-effort function to restore the terminal in a panic. Prefer
+/// [`Builder::panic_hook`](crate::terminal::Builder::panic_hook), which also
+/// knows the enabled input modes and the backend.
 pub fn emergency_restore() {
     let _ = crossterm::terminal::disable_raw_mode();
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::event::DisableMouseCapture,
+        crossterm::event::DisableBracketedPaste,
+        crossterm::event::DisableFocusChange
+    );
     print!("{}", crossterm::cursor::Show);
+    print!(
+        "{}",
+        crossterm::style::SetAttribute(crossterm::style::Attribute::Reset)
+    );
     print!(
         "{}",
         crossterm::style::SetBackgroundColor(crossterm::style::Color::Reset)
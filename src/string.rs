@@ -44,6 +44,7 @@ use std::{
     sync::Arc,
 };
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Graphical string: a string valid to be printed on a terminal for graphic
 /// purpouse.
@@ -116,6 +117,12 @@ impl TermString {
         self.as_str().graphemes(true).count()
     }
 
+    /// Counts how many columns the string takes when displayed, i.e. the sum
+    /// of the widths of its graphemes (see [`TermGrapheme::width`]).
+    pub fn width(&self) -> usize {
+        self.iter().map(|grapheme| grapheme.width()).sum()
+    }
+
     /// Converts into a reference to a plain string.
     pub fn as_str(&self) -> &str {
         &self.alloc[self.range.clone()]
@@ -413,6 +420,12 @@ impl TermGrapheme {
         Self::default()
     }
 
+    /// Number of columns this grapheme takes when displayed: `2` for wide
+    /// graphemes such as CJK characters and most emoji, `1` otherwise.
+    pub fn width(&self) -> usize {
+        self.as_str().width().clamp(1, 2)
+    }
+
     /// Converts into a reference of a plain string.
     pub fn as_str(&self) -> &str {
         &self.tstring
@@ -70,3 +70,13 @@ fn indices_iter() {
     assert_eq!(iter.next(), None);
     assert_eq!(iter.next_back(), None);
 }
+
+#[test]
+fn display_width() {
+    assert_eq!(TermGrapheme::new("a").unwrap().width(), 1);
+    assert_eq!(TermGrapheme::new("ỹ").unwrap().width(), 1);
+    assert_eq!(TermGrapheme::new("漢").unwrap().width(), 2);
+    assert_eq!(TermGrapheme::new("🦀").unwrap().width(), 2);
+    assert_eq!(tstring!["a漢b🦀"].width(), 6);
+    assert_eq!(tstring![""].width(), 0);
+}
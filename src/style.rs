@@ -3,10 +3,11 @@
 use crate::{
     color::{self, Color2},
     coord::{Coord, Vec2},
+    tile::{Attributes, Link},
 };
 
 /// Alignment, margin and other settings for texts.
-#[derive(Debug, Clone, Copy, PartialEq, Eq,Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Style<C = Color2>
 where
     C: color::Updater,
@@ -33,6 +34,10 @@ where
     pub align_denom: Coord,
     /// Foreground-background color pair.
     pub colors: C,
+    /// Text attributes such as bold or underline.
+    pub attributes: Attributes,
+    /// Hyperlink target of the text, if any.
+    pub link: Option<Link>,
 }
 
 impl Default for Style {
@@ -59,6 +64,8 @@ where
             align_numer: 0,
             align_denom: 1,
             colors,
+            attributes: Attributes::NONE,
+            link: None,
         }
     }
 
@@ -79,9 +86,21 @@ where
             align_numer: self.align_numer,
             align_denom: self.align_denom,
             colors,
+            attributes: self.attributes,
+            link: self.link,
         }
     }
 
+    /// Sets text attributes.
+    pub fn attributes(self, attributes: Attributes) -> Self {
+        Self { attributes, ..self }
+    }
+
+    /// Sets the hyperlink target of the text.
+    pub fn link(self, link: Link) -> Self {
+        Self { link: Some(link), ..self }
+    }
+
     /// Sets left margin.
     pub fn left_margin(self, left_margin: Coord) -> Self {
         Self { left_margin, ..self }
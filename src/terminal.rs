@@ -1,20 +1,45 @@
 //! This crate exports a terminal terminal and its utilites.
 
+#[cfg(test)]
+mod test;
+
 use crate::{
-    coord,
-    coord::Vec2,
+    backend::{Backend, CrosstermBackend},
+    color::ColorSupport,
+    coord::{Coord, Vec2},
     error::{AlreadyRunning, Error, ErrorKind, ServicesOff, TaskJoinError},
     event,
-    event::{Event, Reactor},
-    screen::{renderer, Screen, ScreenData},
+    event::{Event, EventLog, EventRecorder, Reactor},
+    screen::{
+        renderer,
+        viewport,
+        FrameHook,
+        FrameStats,
+        InputModes,
+        Recorder,
+        Screen,
+        ScreenData,
+        Stats,
+        StatsCollector,
+    },
 };
 use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
     future::Future,
+    io,
+    panic,
+    process::{Command, ExitStatus},
     sync::{
         atomic::{AtomicBool, Ordering::*},
         Arc,
+        Mutex,
+        MutexGuard,
+        Once,
+        PoisonError,
+        Weak,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::{
     sync::{Barrier, RwLock, RwLockReadGuard, RwLockWriteGuard},
@@ -47,6 +72,90 @@ impl Drop for RunGuard {
     }
 }
 
+tokio::task_local! {
+    /// Identifies the terminal whose services run in the current task, so that
+    /// its panic hook handles only panics in them.
+    static SERVICES_OF: usize;
+}
+
+/// Identifies the terminal of the given shared data.
+fn terminal_id(shared: &Arc<Shared>) -> usize {
+    Arc::as_ptr(shared) as usize
+}
+
+/// Terminals whose services run with the panic hook, by their identifiers.
+static PANIC_HOOK_TERMINALS: Mutex<BTreeMap<usize, Weak<Shared>>> =
+    Mutex::new(BTreeMap::new());
+
+/// The panic hook is installed only once for the whole process, and stays
+/// installed, so that terminals may stop in any order.
+static PANIC_HOOK_INSTALL: Once = Once::new();
+
+/// Locks the terminals whose services run with the panic hook. Since nothing
+/// panics while they are locked, poisoning is ignored.
+fn panic_hook_terminals() -> MutexGuard<'static, BTreeMap<usize, Weak<Shared>>>
+{
+    PANIC_HOOK_TERMINALS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The terminal with the panic hook enabled whose services run in the current
+/// task, if any.
+fn panicking_terminal() -> Option<Arc<Shared>> {
+    let id = SERVICES_OF.try_with(|&id| id).ok()?;
+    panic_hook_terminals().get(&id)?.upgrade()
+}
+
+/// Registers a terminal in the panic hook while its services run. When
+/// dropped, the terminal is unregistered.
+#[derive(Debug)]
+struct PanicHookGuard {
+    /// Identifier of the registered terminal.
+    id: usize,
+}
+
+impl PanicHookGuard {
+    /// Registers the terminal of the given shared data, installing the panic
+    /// hook if not installed yet. If the panic happened in one of a registered
+    /// terminal's tasks, the hook restores the terminal, and then prints the
+    /// panic message and a backtrace. Panics elsewhere, e.g. in tasks spawned
+    /// by the application, are caught by tokio while the terminal keeps
+    /// running, so they are left to the previous hook.
+    fn install(shared: &Arc<Shared>) -> Self {
+        PANIC_HOOK_INSTALL.call_once(|| {
+            let prev = panic::take_hook();
+            panic::set_hook(Box::new(move |info| match panicking_terminal() {
+                Some(shared) => {
+                    shared.screen().cleanup_blocking();
+                    eprintln!("{}\n{}", info, Backtrace::force_capture());
+                },
+                None => prev(info),
+            }));
+        });
+        let id = terminal_id(shared);
+        panic_hook_terminals().insert(id, Arc::downgrade(shared));
+        Self { id }
+    }
+}
+
+impl Drop for PanicHookGuard {
+    fn drop(&mut self) {
+        panic_hook_terminals().remove(&self.id);
+    }
+}
+
+/// Protocol of a desktop notification emitted through the terminal. Support
+/// varies among terminals; unsupported notifications are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NotifyProtocol {
+    /// `OSC 9`, which shows only a message (e.g. iTerm2, Windows Terminal,
+    /// WezTerm). The title and the body are joined into the message.
+    Osc9,
+    /// `OSC 777`, which shows a title and a body (e.g. foot, Ghostty, WezTerm,
+    /// VTE-based terminals with the notification patch).
+    #[default]
+    Osc777,
+}
+
 /// A terminal configuration builder.
 #[derive(Debug, Clone)]
 pub struct Builder {
@@ -56,6 +165,44 @@ pub struct Builder {
     frame_time: Duration,
     /// Interval between a failed poll and the next poll.
     event_interval: Duration,
+    /// Maximum number of unread events kept by the event channel.
+    event_capacity: usize,
+    /// What the event channel does when it is full.
+    event_overflow: event::Overflow,
+    /// Whether mouse events are captured.
+    mouse_capture: bool,
+    /// Whether bracketed paste is enabled.
+    bracketed_paste: bool,
+    /// Whether focus changes are reported.
+    focus_reporting: bool,
+    /// Frame interval time while the terminal is not focused.
+    unfocused_frame_time: Option<Duration>,
+    /// The terminal's backend.
+    backend: Arc<dyn Backend>,
+    /// Where the output is recorded as asciicast, if anywhere.
+    recorder: Option<Arc<Recorder>>,
+    /// Where delivered events are logged, if anywhere.
+    event_recorder: Option<Arc<EventRecorder>>,
+    /// Event log replayed instead of the backend's events, if any.
+    replay: Option<Arc<EventLog>>,
+    /// Whether time is counted in frames by a fixed frame clock.
+    frame_clock: bool,
+    /// Whether a panic hook restoring the terminal is installed.
+    panic_hook: bool,
+    /// Whether Ctrl-Z suspends the process instead of being delivered.
+    suspend_on_ctrl_z: bool,
+    /// Number of rows reserved below the cursor, if rendering inline.
+    inline: Option<Coord>,
+    /// Colors supported by the terminal, if not detected by the backend.
+    color_support: Option<ColorSupport>,
+    /// Whether frames are synchronized updates.
+    synchronized_output: bool,
+    /// Whether trailing blank tiles are erased with `EL`.
+    erase_lines: bool,
+    /// Whether repeated graphemes are written with `REP`.
+    repeat_graphemes: bool,
+    /// Function called with the statistics of every frame, if any.
+    frame_hook: Option<Arc<FrameHook>>,
 }
 
 impl Default for Builder {
@@ -71,6 +218,25 @@ impl Builder {
             min_screen: Vec2 { x: 80, y: 25 },
             frame_time: Duration::from_millis(20),
             event_interval: Duration::from_millis(20),
+            event_capacity: 256,
+            event_overflow: event::Overflow::default(),
+            mouse_capture: false,
+            bracketed_paste: false,
+            focus_reporting: false,
+            unfocused_frame_time: None,
+            backend: Arc::new(CrosstermBackend::new()),
+            recorder: None,
+            event_recorder: None,
+            replay: None,
+            frame_clock: false,
+            panic_hook: false,
+            suspend_on_ctrl_z: false,
+            inline: None,
+            color_support: None,
+            synchronized_output: true,
+            erase_lines: false,
+            repeat_graphemes: false,
+            frame_hook: None,
         }
     }
 
@@ -79,7 +245,9 @@ impl Builder {
         Self { min_screen, ..self }
     }
 
-    /// Builds the rate that the screen is updated.
+    /// Builds the minimum time between two frames (default 20ms). Frames are
+    /// only rendered when the screen changes, at most once per `frame_time`;
+    /// changes made meanwhile are rendered together in the next frame.
     pub fn frame_time(self, frame_time: Duration) -> Self {
         Self { frame_time, ..self }
     }
@@ -89,6 +257,209 @@ impl Builder {
         Self { event_interval, ..self }
     }
 
+    /// Maximum number of unread events kept by the event channel (default
+    /// 256). Ignored if the overflow policy is [`event::Overflow::KeepLatest`].
+    pub fn event_capacity(self, event_capacity: usize) -> Self {
+        Self { event_capacity, ..self }
+    }
+
+    /// What the event channel does when an event arrives and there are already
+    /// `event_capacity` unread events (default
+    /// [`event::Overflow::DropOldest`]). Use [`event::Overflow::KeepLatest`]
+    /// to keep only the newest event of each kind.
+    pub fn event_overflow(self, event_overflow: event::Overflow) -> Self {
+        Self { event_overflow, ..self }
+    }
+
+    /// Whether mouse events ([`event::MouseEvent`]) should be captured and
+    /// reported (default `false`). While captured, the terminal's own mouse
+    /// handling, such as text selection, is usually unavailable.
+    pub fn mouse_capture(self, mouse_capture: bool) -> Self {
+        Self { mouse_capture, ..self }
+    }
+
+    /// Whether bracketed paste should be enabled (default `false`). When
+    /// enabled, text pasted into the terminal is reported as a single
+    /// [`Event::Paste`] instead of a sequence of key events.
+    pub fn bracketed_paste(self, bracketed_paste: bool) -> Self {
+        Self { bracketed_paste, ..self }
+    }
+
+    /// Whether the terminal should report focus changes as [`Event::Focus`]
+    /// (default `false`).
+    pub fn focus_reporting(self, focus_reporting: bool) -> Self {
+        Self { focus_reporting, ..self }
+    }
+
+    /// Builds the minimum time between two frames while the terminal is not
+    /// focused (default: same as `frame_time`). Only has effect if focus
+    /// reporting is enabled.
+    pub fn unfocused_frame_time(self, unfocused_frame_time: Duration) -> Self {
+        Self { unfocused_frame_time: Some(unfocused_frame_time), ..self }
+    }
+
+    /// Builds the backend of the terminal, i.e. where size, raw mode and events
+    /// come from, and where output goes to (default
+    /// [`CrosstermBackend`], the process' own terminal).
+    pub fn backend<B>(self, backend: B) -> Self
+    where
+        B: Backend,
+    {
+        Self { backend: Arc::new(backend), ..self }
+    }
+
+    /// Records the session as an asciicast v2 file (as used by asciinema) into
+    /// the given writer (default: no recording). Everything rendered is
+    /// written as output events, and resizes as resize events. The writer is
+    /// written in a background thread, and flushed when the terminal is
+    /// cleaned up. Consider buffering it, e.g. with [`std::io::BufWriter`].
+    ///
+    /// Recording is best-effort: if writing fails (e.g. the disk is full),
+    /// the recording stops, the terminal keeps running, and the error is
+    /// printed to the standard error once the terminal is cleaned up.
+    ///
+    /// Since the writer is shared between clones of this builder, every run
+    /// of them records into the same writer.
+    pub fn record_asciicast<W>(self, writer: W) -> Self
+    where
+        W: io::Write + Send + 'static,
+    {
+        Self { recorder: Some(Arc::new(Recorder::new(writer))), ..self }
+    }
+
+    /// Logs every event delivered to the application into the given writer
+    /// (default: no logging), together with its time and epoch (see
+    /// [`EventLog`]). Events discarded by the event channel (see
+    /// [`Builder::event_overflow`]) are not logged. The log can be parsed with
+    /// [`EventLog::read`] and replayed with [`Builder::replay_events`].
+    ///
+    /// Logging is best-effort: the writer is written in a background thread,
+    /// and if writing fails, the logging stops, the terminal keeps running,
+    /// and the error is printed to the standard error once the terminal is
+    /// cleaned up.
+    ///
+    /// Since the writer is shared between clones of this builder, every run
+    /// of them logs into the same writer.
+    pub fn record_events<W>(self, writer: W) -> Self
+    where
+        W: io::Write + Send + 'static,
+    {
+        let event_recorder = Some(Arc::new(EventRecorder::new(writer)));
+        Self { event_recorder, ..self }
+    }
+
+    /// Replays the given event log (default: no replay). The logged events are
+    /// delivered at their logged times, instead of the backend's events;
+    /// after the last one, the backend's events are delivered again. Together
+    /// with the same settings and screen size as the recorded session, the
+    /// application receives the same events in the same order.
+    ///
+    /// By the wall clock, replay is not deterministic, though: an application
+    /// whose behavior depends on timing (e.g. on how many events arrive before
+    /// it reads them, or on how many frames are rendered) may behave
+    /// differently. With [`Builder::frame_clock`], events are delivered at
+    /// their logged frames instead, so that an application stepping through
+    /// [`Terminal::next_frame`] is replayed exactly.
+    pub fn replay_events(self, log: EventLog) -> Self {
+        Self { replay: Some(Arc::new(log)), ..self }
+    }
+
+    /// Whether time is counted in frames by a fixed frame clock (default
+    /// `false`). The clock ticks once every `frame_time` (late ticks are
+    /// delayed, not made up for), and frames are rendered only at ticks, if
+    /// anything changed. Events are delivered at ticks as well: events
+    /// arriving meanwhile are held until the next one. Logged events (see
+    /// [`Builder::record_events`]) record the frame they were delivered in,
+    /// and are replayed at the same frame.
+    ///
+    /// An application steps through frames with [`Terminal::next_frame`]
+    /// instead of its own timers, so that, as long as it handles each frame
+    /// before the next tick, it sees the same events at the same frames when
+    /// its session is replayed.
+    pub fn frame_clock(self, frame_clock: bool) -> Self {
+        Self { frame_clock, ..self }
+    }
+
+    /// Whether a panic hook should be installed while the terminal services
+    /// run (default `false`). On a panic in one of the terminal's tasks
+    /// (including the application's), the hook restores the terminal (raw
+    /// mode, alternate screen, input modes...) just like a regular cleanup.
+    /// It then prints the panic message and a backtrace on the normal screen,
+    /// regardless of `RUST_BACKTRACE`. Panics in other tasks and threads are
+    /// left to the previously installed hook. The hook is installed once for
+    /// the whole process and stays installed, handling every terminal with
+    /// this option whose services run.
+    pub fn panic_hook(self, panic_hook: bool) -> Self {
+        Self { panic_hook, ..self }
+    }
+
+    /// Whether Ctrl-Z should suspend the process, as in a shell, instead of
+    /// being delivered as a key event (default `false`). In raw mode, the
+    /// terminal does not stop the process by itself. See
+    /// [`Terminal::suspend`].
+    pub fn suspend_on_ctrl_z(self, suspend_on_ctrl_z: bool) -> Self {
+        Self { suspend_on_ctrl_z, ..self }
+    }
+
+    /// Renders inline, in the given number of rows below the shell's cursor,
+    /// instead of in the alternate screen (default: alternate screen). The
+    /// screen is as wide as the terminal and has the given height (or the
+    /// terminal's, if smaller); cursor movement is relative to it. On exit,
+    /// the last frame is kept in the scrollback, and the cursor is left in
+    /// the line below it, so that it can be used for prompts in ordinary
+    /// command-line tools (e.g. with [`Menu`](crate::ui::menu::Menu) or
+    /// [`InputDialog`](crate::ui::input::InputDialog)). Beware that the
+    /// minimum screen height should not be greater than `rows`.
+    pub fn inline(self, rows: Coord) -> Self {
+        Self { inline: Some(rows), ..self }
+    }
+
+    /// Overrides the colors supported by the terminal (default: detected by
+    /// the backend, from `NO_COLOR`, `COLORTERM` and `TERM` for the default
+    /// backend). Colors not supported are approximated when rendered.
+    pub fn color_support(self, color_support: ColorSupport) -> Self {
+        Self { color_support: Some(color_support), ..self }
+    }
+
+    /// Whether each frame is written as a synchronized update (DEC mode 2026),
+    /// which the terminal shows at once, so that half-written frames are
+    /// never shown (default `true`). Terminals which do not support it ignore
+    /// it, so it is not asked for.
+    pub fn synchronized_output(self, synchronized_output: bool) -> Self {
+        Self { synchronized_output, ..self }
+    }
+
+    /// Whether trailing blank tiles of a row are erased at once with the `EL`
+    /// sequence, when cheaper than writing them (default `false`). Saves bytes
+    /// on mostly empty screens, but terminals which do not support background
+    /// color erase (`bce`, e.g. `screen` and `tmux` with some settings) would
+    /// erase with the default background instead of the tiles' one. Always
+    /// done without colors.
+    pub fn erase_lines(self, erase_lines: bool) -> Self {
+        Self { erase_lines, ..self }
+    }
+
+    /// Whether runs of a repeated grapheme are written with the `REP`
+    /// sequence, which repeats the last written character, when cheaper than
+    /// writing them (default `false`). Saves bytes over slow connections, but
+    /// terminals which do not support `REP` (e.g. the Linux console) would
+    /// show blanks instead.
+    pub fn repeat_graphemes(self, repeat_graphemes: bool) -> Self {
+        Self { repeat_graphemes, ..self }
+    }
+
+    /// Calls the given function with the statistics of every frame, e.g. to
+    /// tune a game loop or to spot contention between the application and the
+    /// renderer (default: none). The function runs in the renderer, while the
+    /// screen is locked, so it should be quick and must not lock the terminal.
+    /// See also [`Terminal::stats`].
+    pub fn on_frame<F>(self, callback: F) -> Self
+    where
+        F: Fn(&FrameStats) + Send + Sync + 'static,
+    {
+        Self { frame_hook: Some(Arc::new(FrameHook::new(callback))), ..self }
+    }
+
     /// Starts the application and gives it a handle to the terminal. When the
     /// given start function finishes, the application's execution stops as
     /// well.
@@ -99,57 +470,89 @@ impl Builder {
     /// not detaching.
     ///
     /// Returns an [`AlreadyRunning`] error if there is already an instance of
-    /// terminal services executing. In other words, one should not call
-    /// this function again if another call did not finish yet, otherwise it
-    /// will panic.
+    /// terminal services executing with an exclusive backend (such as the
+    /// default one). In other words, one should not call this function again
+    /// if another call did not finish yet, otherwise it will panic.
     ///
     /// Beware! If the given `start` future returns a `Result`, then `run` will
     /// return a double `Result`!!
     pub async fn run<F, A, T>(self, start: F) -> Result<T, Error>
+    where
+        F: FnOnce(Terminal) -> A + Send + 'static,
+        A: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (ret, _) = self.run_shared(start).await?;
+        Ok(ret)
+    }
+
+    /// Like [`Builder::run`], but also returns the shared data, so that the
+    /// final state of the terminal can be inspected.
+    pub(crate) async fn run_shared<F, A, T>(
+        self,
+        start: F,
+    ) -> Result<(T, Arc<Shared>), Error>
     where
         F: FnOnce(Terminal) -> A + Send + 'static,
         A: Future<Output = T> + Send + 'static,
         T: Send + 'static,
     {
         // Ensures there are no other terminal sevices executing.
-        let _guard = RunGuard::acquire()?;
+        let _guard = if self.backend.exclusive() {
+            Some(RunGuard::acquire()?)
+        } else {
+            None
+        };
 
         // Initializes terminal structures.
         let initial_size = self.initial_size()?;
         let terminal = self.finish(initial_size).await;
         let shared = terminal.shared.clone();
-        shared.screen().setup().await?;
+        let panic_guard = if self.panic_hook {
+            Some(PanicHookGuard::install(&shared))
+        } else {
+            None
+        };
+        shared.screen().setup(initial_size).await?;
 
         // Synchronization between parties.
         let barrier = Arc::new(Barrier::new(3));
+        let id = terminal_id(&shared);
 
         // Event listener task future.
         let events_fut = {
             let interval = self.event_interval;
             let barrier = barrier.clone();
             let shared = shared.clone();
-            tokio::spawn(events_task(barrier, interval, shared, initial_size))
+            let task = events_task(barrier, interval, shared, initial_size);
+            tokio::spawn(SERVICES_OF.scope(id, task))
         };
 
         // Renderer task future.
         let renderer_fut = {
             let barrier = barrier.clone();
             let shared = shared.clone();
-            tokio::spawn(renderer_task(barrier, shared))
+            tokio::spawn(SERVICES_OF.scope(id, renderer_task(barrier, shared)))
         };
 
         // Main task future.
         let main_fut = {
             let barrier = barrier.clone();
-            tokio::spawn(main_task(barrier, terminal, start))
+            tokio::spawn(
+                SERVICES_OF.scope(id, main_task(barrier, terminal, start)),
+            )
         };
 
         // Joins every task.
         let (main_ret, events_ret, renderer_ret) =
             tokio::join!(main_fut, events_fut, renderer_fut);
+        drop(panic_guard);
 
         // Cleans up screen configurations (such as raw mode).
         let _ = shared.screen().cleanup().await;
+        if let Some(recorder) = shared.event_recorder() {
+            task::block_in_place(|| recorder.finish());
+        }
 
         // Matches the error of events task result.
         if let Err(error) = events_ret.map_err(TaskJoinError::new)? {
@@ -168,29 +571,23 @@ impl Builder {
         }
 
         // Finally returns main task return value.
-        Ok(main_ret.map_err(TaskJoinError::new)?)
+        Ok((main_ret.map_err(TaskJoinError::new)?, shared))
     }
 
     /// Finds the initial size of the terminal.
     fn initial_size(&self) -> Result<Vec2, Error> {
-        let (width, height) = task::block_in_place(|| {
-            crossterm::terminal::enable_raw_mode()?;
-            crossterm::terminal::size()
+        let size = task::block_in_place(|| {
+            self.backend.enable_raw_mode()?;
+            self.backend.size()
         })?;
-        Ok(Vec2 {
-            y: coord::from_crossterm(height),
-            x: coord::from_crossterm(width),
-        })
+        Ok(viewport(self.inline, size))
     }
 
     /// Finishes the builder and produces a terminal handle.
     async fn finish(&self, screen_size: Vec2) -> Terminal {
-        let shared = Arc::new(Shared::new(
-            screen_size,
-            self.min_screen,
-            self.frame_time,
-        ));
-        Terminal { shared, curr_epoch: 0 }
+        let shared = Arc::new(Shared::new(screen_size, self));
+        shared.events().add_reader(0);
+        Terminal { shared, curr_epoch: 0, frame: 0 }
     }
 }
 
@@ -206,8 +603,8 @@ where
     A: Future<Output = T> + Send + 'static,
     T: Send + 'static,
 {
-    let cloned = terminal.clone();
-    let _guard = cloned.shared.conn_guard();
+    let shared = terminal.shared.clone();
+    let _guard = shared.conn_guard();
     barrier.wait().await;
     start(terminal).await
 }
@@ -238,18 +635,28 @@ async fn renderer_task(
 }
 
 /// An application's handle to the terminal.
-#[derive(Debug, Clone)]
+///
+/// Each handle reads events on its own: a clone starts at the same event as
+/// the original handle, and an event is discarded only once every handle has
+/// read it (or to make room for new events, according to
+/// [`event::Overflow`]). Handles that do not read events should be dropped,
+/// since unread events are kept for them, and, with
+/// [`event::Overflow::DropNewest`], new events are discarded once the queue is
+/// full.
+#[derive(Debug)]
 pub struct Terminal {
     /// Shared data between application's terminal handles and services.
     shared: Arc<Shared>,
     /// Current epoch on event channel for this channel.
     curr_epoch: event::Epoch,
+    /// Last frame waited for through [`Terminal::next_frame`].
+    frame: u64,
 }
 
 impl Terminal {
     /// Runs the terminal application with the default settings, i.e. minumum
-    /// screen is 80x25, 20ms for frame rendering interval, and 20ms for event
-    /// polling from the OS.
+    /// screen is 80x25, 20ms as the minimum time between frames, and 20ms for
+    /// event polling from the OS.
     ///
     /// Gives the application a handle to the terminal. When the given start
     /// function finishes, the application's execution stops as well.
@@ -290,26 +697,124 @@ impl Terminal {
             screen,
             _guard: guard,
             event,
+            events: self.shared.events(),
             curr_epoch: &mut self.curr_epoch,
         })
     }
 
     /// Listens for an event and only finishes when an event arrives. A locked
     /// terminal handle is returned, on which an application can write to the
-    /// screen or get the most recent event, which will be present.
+    /// screen or get the oldest unread event, which will be present. If there
+    /// already are unread events, this returns immediately.
     ///
     /// Screen is locked, event channel is locked.
     pub async fn listen<'terminal>(
         &'terminal mut self,
     ) -> Result<TerminalGuard<'terminal>, ServicesOff> {
-        self.shared.events.subscribe().await;
+        loop {
+            let notified = self.shared.events().subscribe();
+            let pending = self.shared.events().read(self.curr_epoch).is_some();
+            if pending || !self.shared.is_connected() {
+                break;
+            }
+            notified.await;
+        }
         self.lock_now().await
     }
 
-    /// Clears the event channel. After this call, the current event is marked
+    /// Suspends the process, as a shell does with Ctrl-Z (`SIGTSTP`). The
+    /// terminal is restored (raw mode, alternate screen, input modes...) and
+    /// the process is stopped. Once it is continued (`SIGCONT`, e.g. the
+    /// shell's `fg`), the terminal is set up again and the whole screen is
+    /// redrawn, so the application does not need to render it again.
+    ///
+    /// The process is only stopped on unix; elsewhere, this fails with an
+    /// unsupported IO error, leaving the terminal set up.
+    pub async fn suspend(&mut self) -> Result<(), Error> {
+        let mut session = self.lock_now().await?;
+        session.screen().suspend().await
+    }
+
+    /// Runs an external program (e.g. the user's `$EDITOR`) with inherited
+    /// standard input and output, waiting for it to exit. Meanwhile, rendering
+    /// and event polling are paused and the terminal is restored, just like
+    /// after the application finishes. Afterwards, the terminal is set up
+    /// again and the whole screen is redrawn, so the application does not
+    /// need to render it again. The terminal is set up again even if the
+    /// program could not be run.
+    ///
+    /// The program is waited for in a blocking section of the calling task
+    /// (see [`tokio::task::block_in_place`], which requires the multi-threaded
+    /// runtime), holding the screen: other tasks locking the terminal wait
+    /// until the program exits.
+    pub async fn run_external(
+        &mut self,
+        mut command: Command,
+    ) -> Result<ExitStatus, Error> {
+        let mut session = self.lock_now().await?;
+        session.screen().while_restored(|_| command.status()).await
+    }
+
+    /// Sets the window title of the terminal. The previous title is saved the
+    /// first time, and restored when the terminal is cleaned up or through
+    /// [`Terminal::restore_title`]. Control characters are removed.
+    ///
+    /// Like the bell and notifications, this does not wait for the output:
+    /// while it is busy (e.g. the screen is too small), the title is written
+    /// with the next frame.
+    pub async fn set_title(&self, title: &str) -> Result<(), Error> {
+        self.check_connected()?;
+        self.shared.screen().set_title(title).await
+    }
+
+    /// Restores the window title previous to [`Terminal::set_title`]. Does
+    /// nothing if the title was not set.
+    pub async fn restore_title(&self) -> Result<(), Error> {
+        self.check_connected()?;
+        self.shared.screen().restore_title().await
+    }
+
+    /// Rings the terminal's bell.
+    pub async fn bell(&self) -> Result<(), Error> {
+        self.check_connected()?;
+        self.shared.screen().bell().await
+    }
+
+    /// Emits a desktop notification with the given title and body, using the
+    /// given protocol. Control characters are removed.
+    pub async fn notify(
+        &self,
+        protocol: NotifyProtocol,
+        title: &str,
+        body: &str,
+    ) -> Result<(), Error> {
+        self.check_connected()?;
+        self.shared.screen().notify_desktop(protocol, title, body).await
+    }
+
+    /// Statistics of the frames rendered so far: how many, how many were
+    /// dropped, bytes written, changed tiles, time spent producing them, and
+    /// time the application held the terminal locked. See also
+    /// [`Builder::on_frame`].
+    pub fn stats(&self) -> Stats {
+        self.shared.screen().stats().totals()
+    }
+
+    /// Fails if the terminal services are no longer running.
+    fn check_connected(&self) -> Result<(), ServicesOff> {
+        if self.shared.is_connected() {
+            Ok(())
+        } else {
+            Err(ServicesOff)
+        }
+    }
+
+    /// Clears the event channel. After this call, every unread event is marked
     /// as read and it will no longer be available.
     pub fn clear_event(&mut self) {
+        let prev = self.curr_epoch;
         self.curr_epoch = self.shared.events().epoch();
+        self.shared.events().mark_read(prev, self.curr_epoch);
     }
 
     /// Waits for user input before continuing, but waits for some given time
@@ -323,6 +828,46 @@ impl Terminal {
         self.clear_event();
         self.listen().await
     }
+
+    /// Waits for the frame after the last one waited for by this handle, and
+    /// returns its number, starting at 1. With the fixed frame clock (see
+    /// [`Builder::frame_clock`]), the frame is the clock's, and waiting
+    /// finishes once its events are delivered; frames are never skipped, so
+    /// if the application falls behind, this returns immediately until it
+    /// catches up. Without it, this only waits for the frame time.
+    pub async fn next_frame(&mut self) -> Result<u64, ServicesOff> {
+        let disconnected = self.shared.screen().subscribe();
+        self.check_connected()?;
+        self.frame += 1;
+        match self.shared.screen().clock() {
+            Some(clock) => {
+                tokio::select! {
+                    _ = clock.wait_ready(self.frame) => (),
+                    _ = disconnected => (),
+                }
+                self.check_connected()?;
+            },
+            None => time::sleep(self.shared.screen().curr_frame_time()).await,
+        }
+        Ok(self.frame)
+    }
+}
+
+impl Clone for Terminal {
+    fn clone(&self) -> Self {
+        self.shared.events().add_reader(self.curr_epoch);
+        Self {
+            shared: self.shared.clone(),
+            curr_epoch: self.curr_epoch,
+            frame: self.frame,
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.shared.events().remove_reader(self.curr_epoch);
+    }
 }
 
 /// A guard on a locked terminal handle.
@@ -334,6 +879,8 @@ pub struct TerminalGuard<'terminal> {
     _guard: AppSyncGuard<'terminal>,
     /// The result of reading the event channel (event epoch, event itself).
     event: Option<(event::Epoch, Event)>,
+    /// The event channel, so events can be marked as read.
+    events: &'terminal event::Channel,
     /// Reference to the current epoch so we can update it when the event is
     /// read.
     curr_epoch: &'terminal mut event::Epoch,
@@ -342,12 +889,14 @@ pub struct TerminalGuard<'terminal> {
 }
 
 impl<'terminal> TerminalGuard<'terminal> {
-    /// Reads the last event. When this method is called, the event is marked as
-    /// read, and in subsequent locks to the terminal handle, it won't be
-    /// available. If not called and no other event arrives, this will not
-    /// be marked as read and it will be available in the next locking.
+    /// Reads the oldest unread event. When this method is called, the event is
+    /// marked as read, and in subsequent locks to the terminal handle, it won't
+    /// be available. If not called, this will not be marked as read and it
+    /// will be available in the next locking.
     pub fn event(&mut self) -> Option<Event> {
-        self.event.map(|(new_epoch, event)| {
+        let events = self.events;
+        self.event.clone().map(|(new_epoch, event)| {
+            events.mark_read(*self.curr_epoch, new_epoch);
             *self.curr_epoch = new_epoch;
             event
         })
@@ -374,21 +923,53 @@ pub(crate) struct Shared {
     screen: ScreenData,
     /// Events channel data.
     events: event::Channel,
+    /// The terminal's backend.
+    backend: Arc<dyn Backend>,
+    /// Where delivered events are logged, if anywhere.
+    event_recorder: Option<Arc<EventRecorder>>,
+    /// Event log replayed instead of the backend's events, if any.
+    replay: Option<Arc<EventLog>>,
+    /// Whether Ctrl-Z suspends the process instead of being delivered.
+    suspend_on_ctrl_z: bool,
 }
 
 impl Shared {
-    /// Creates shared data from: current screen size, minimum screen size,
-    /// frame interval time .
-    pub fn new(
-        screen_size: Vec2,
-        min_screen: Vec2,
-        frame_time: Duration,
-    ) -> Self {
+    /// Creates shared data from the current screen size and the settings of
+    /// the given builder.
+    pub fn new(screen_size: Vec2, builder: &Builder) -> Self {
         Self {
             sync: RwLock::new(()),
             connected: AtomicBool::new(true),
-            screen: ScreenData::new(screen_size, min_screen, frame_time),
-            events: event::Channel::default(),
+            screen: ScreenData::new(
+                screen_size,
+                builder.min_screen,
+                builder.frame_time,
+                builder.unfocused_frame_time,
+                builder.frame_clock,
+                InputModes {
+                    mouse_capture: builder.mouse_capture,
+                    bracketed_paste: builder.bracketed_paste,
+                    focus_reporting: builder.focus_reporting,
+                },
+                builder.inline,
+                builder
+                    .color_support
+                    .unwrap_or_else(|| builder.backend.color_support()),
+                builder.synchronized_output,
+                builder.erase_lines,
+                builder.repeat_graphemes,
+                builder.backend.clone(),
+                builder.recorder.clone(),
+                builder.frame_hook.clone(),
+            ),
+            events: event::Channel::new(
+                builder.event_capacity,
+                builder.event_overflow,
+            ),
+            backend: builder.backend.clone(),
+            event_recorder: builder.event_recorder.clone(),
+            replay: builder.replay.clone(),
+            suspend_on_ctrl_z: builder.suspend_on_ctrl_z,
         }
     }
 
@@ -423,7 +1004,11 @@ impl Shared {
     pub async fn app_guard<'this>(
         &'this self,
     ) -> Result<AppSyncGuard<'this>, ServicesOff> {
-        let guard = AppSyncGuard { _inner: self.sync.write().await };
+        let guard = AppSyncGuard {
+            _inner: self.sync.write().await,
+            since: Instant::now(),
+            stats: self.screen.stats(),
+        };
         if self.is_connected() {
             Ok(guard)
         } else {
@@ -436,6 +1021,26 @@ impl Shared {
         &self.events
     }
 
+    /// Returns reference to the backend used by this application.
+    pub fn backend(&self) -> &dyn Backend {
+        &*self.backend
+    }
+
+    /// Returns where delivered events are logged, if anywhere.
+    pub fn event_recorder(&self) -> Option<&EventRecorder> {
+        self.event_recorder.as_deref()
+    }
+
+    /// Returns the event log replayed instead of the backend's events, if any.
+    pub fn replay(&self) -> Option<&EventLog> {
+        self.replay.as_deref()
+    }
+
+    /// Whether Ctrl-Z suspends the process instead of being delivered.
+    pub fn suspends_on_ctrl_z(&self) -> bool {
+        self.suspend_on_ctrl_z
+    }
+
     /// Returns reference to the screen manager's data used by this application.
     pub fn screen(&self) -> &ScreenData {
         &self.screen
@@ -460,6 +1065,16 @@ pub(crate) struct ServiceSyncGuard<'shared> {
 pub(crate) struct AppSyncGuard<'shared> {
     /// Inner lock guard.
     _inner: RwLockWriteGuard<'shared, ()>,
+    /// When the guard was acquired.
+    since: Instant,
+    /// Where the time this guard was held is recorded.
+    stats: &'shared StatsCollector,
+}
+
+impl<'shared> Drop for AppSyncGuard<'shared> {
+    fn drop(&mut self) {
+        self.stats.app_unlocked(self.since.elapsed());
+    }
 }
 
 /// Connection guard for shared data. Disconnects when dropped.
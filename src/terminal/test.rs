@@ -0,0 +1,37 @@
+use crate::{
+    coord::Vec2,
+    headless::HeadlessBackend,
+    terminal::{
+        panicking_terminal,
+        terminal_id,
+        Builder,
+        PanicHookGuard,
+        Shared,
+        SERVICES_OF,
+    },
+};
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn panic_hook_guards_dropped_out_of_order() {
+    let size = Vec2 { x: 20, y: 4 };
+    let builder = Builder::new().backend(HeadlessBackend::new(size));
+    let first = Arc::new(Shared::new(size, &builder));
+    let second = Arc::new(Shared::new(size, &builder));
+    let handled = |shared: &Arc<Shared>| {
+        SERVICES_OF
+            .sync_scope(terminal_id(shared), panicking_terminal)
+            .is_some()
+    };
+
+    let first_guard = PanicHookGuard::install(&first);
+    let second_guard = PanicHookGuard::install(&second);
+    assert!(handled(&first));
+    assert!(handled(&second));
+
+    drop(first_guard);
+    assert!(!handled(&first));
+    assert!(handled(&second));
+    drop(second_guard);
+    assert!(!handled(&second));
+}
@@ -0,0 +1,28 @@
+//! This module provides helpers shared by the tests of several modules.
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+/// A writer into memory whose copies share the written bytes, so that they
+/// can be read after another copy is given away (e.g. to a recording).
+#[derive(Debug, Clone, Default)]
+pub struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl SharedWriter {
+    /// Everything written so far, as text.
+    pub fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
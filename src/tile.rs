@@ -2,10 +2,19 @@
 //! character with foreground and background colors, corresponding to a graphic
 //! unit.
 
+#[cfg(test)]
+mod test;
+
 use crate::{
     color::{ApproxBrightness, Color, Color2},
     string::TermGrapheme,
 };
+use crossterm::style::Attribute as CrosstermAttr;
+use std::{
+    fmt,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign},
+    sync::Arc,
+};
 
 /// A [`Tile`] in the terminal, i.e. a single character with foreground and
 /// background colors.
@@ -15,6 +24,256 @@ pub struct Tile {
     pub grapheme: TermGrapheme,
     /// The foreground-background pair of colors.
     pub colors: Color2,
+    /// Text attributes such as bold or underline.
+    pub attributes: Attributes,
+    /// Hyperlink target of this [`Tile`], if any.
+    pub link: Option<Link>,
+}
+
+/// A hyperlink target, shown by the terminal as a clickable link (`OSC 8`).
+/// Not every terminal supports hyperlinks; unsupported ones show the text
+/// only.
+///
+/// Links are cheap to clone, since the URL is shared. Links are compared by
+/// their URLs.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Link {
+    /// The URL, without control characters.
+    url: Arc<str>,
+}
+
+impl Link {
+    /// Creates a link to the given URL. Control characters are removed, since
+    /// they would break the terminal sequence.
+    pub fn new(url: &str) -> Self {
+        let url = url.chars().filter(|ch| !ch.is_control()).collect::<String>();
+        Self { url: Arc::from(url) }
+    }
+
+    /// The URL this link targets.
+    pub fn url(&self) -> Arc<str> {
+        self.url.clone()
+    }
+
+    /// Writes the sequence starting this link, or ending any link if `None`.
+    pub(crate) fn write_change<W>(
+        link: Option<&Self>,
+        buf: &mut W,
+    ) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match link {
+            Some(link) => write!(buf, "\x1b]8;;{}\x1b\\", link.url),
+            None => buf.write_str("\x1b]8;;\x1b\\"),
+        }
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_tuple("Link").field(&self.url).finish()
+    }
+}
+
+/// A set of text attributes, such as bold, italic or underline. Attributes are
+/// combined with `|`, e.g. `Attributes::BOLD | Attributes::UNDERLINE`.
+///
+/// Not every terminal supports every attribute; unsupported ones are usually
+/// ignored.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Attributes {
+    /// Bits of the set attributes.
+    bits: u8,
+}
+
+impl Attributes {
+    /// No attribute at all.
+    pub const NONE: Self = Self { bits: 0 };
+    /// Bold or increased intensity.
+    pub const BOLD: Self = Self { bits: 1 << 0 };
+    /// Italic text.
+    pub const ITALIC: Self = Self { bits: 1 << 1 };
+    /// Underlined text.
+    pub const UNDERLINE: Self = Self { bits: 1 << 2 };
+    /// Foreground and background colors swapped.
+    pub const REVERSE: Self = Self { bits: 1 << 3 };
+    /// Dim or decreased intensity.
+    pub const DIM: Self = Self { bits: 1 << 4 };
+    /// Crossed-out text.
+    pub const STRIKETHROUGH: Self = Self { bits: 1 << 5 };
+    /// Every attribute.
+    pub const ALL: Self = Self { bits: (1 << 6) - 1 };
+
+    /// Each single attribute, its name and the crossterm attributes turning it
+    /// on and off.
+    const TABLE: [(Self, &'static str, CrosstermAttr, CrosstermAttr); 6] = [
+        (
+            Self::BOLD,
+            "BOLD",
+            CrosstermAttr::Bold,
+            CrosstermAttr::NormalIntensity,
+        ),
+        (
+            Self::ITALIC,
+            "ITALIC",
+            CrosstermAttr::Italic,
+            CrosstermAttr::NoItalic,
+        ),
+        (
+            Self::UNDERLINE,
+            "UNDERLINE",
+            CrosstermAttr::Underlined,
+            CrosstermAttr::NoUnderline,
+        ),
+        (
+            Self::REVERSE,
+            "REVERSE",
+            CrosstermAttr::Reverse,
+            CrosstermAttr::NoReverse,
+        ),
+        (Self::DIM, "DIM", CrosstermAttr::Dim, CrosstermAttr::NormalIntensity),
+        (
+            Self::STRIKETHROUGH,
+            "STRIKETHROUGH",
+            CrosstermAttr::CrossedOut,
+            CrosstermAttr::NotCrossedOut,
+        ),
+    ];
+
+    /// Creates attributes from raw bits. Unknown bits are ignored.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self { bits: bits & Self::ALL.bits }
+    }
+
+    /// Raw bits of these attributes.
+    pub const fn bits(self) -> u8 {
+        self.bits
+    }
+
+    /// Whether no attribute is set.
+    pub const fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Whether all attributes in `other` are set in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// Whether any attribute in `other` is set in `self`.
+    pub const fn intersects(self, other: Self) -> bool {
+        self.bits & other.bits != 0
+    }
+
+    /// Sets the given attributes.
+    pub fn insert(&mut self, other: Self) {
+        *self |= other;
+    }
+
+    /// Unsets the given attributes.
+    pub fn remove(&mut self, other: Self) {
+        *self -= other;
+    }
+
+    /// Writes the SGR sequences that change the terminal's attributes from
+    /// `prev` to `self`, emitting only what differs.
+    pub(crate) fn write_diff<W>(self, prev: Self, buf: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        let intensity = Self::BOLD | Self::DIM;
+        let mut removed = prev - self;
+        let mut added = self - prev;
+        // Bold and dim share the same "off" sequence, which turns off both.
+        if removed.intersects(intensity) {
+            removed -= intensity;
+            added |= self & intensity;
+            let attr = CrosstermAttr::NormalIntensity;
+            write!(buf, "{}", crossterm::style::SetAttribute(attr))?;
+        }
+
+        for &(single, _, on, off) in &Self::TABLE {
+            if removed.contains(single) {
+                write!(buf, "{}", crossterm::style::SetAttribute(off))?;
+            }
+            if added.contains(single) {
+                write!(buf, "{}", crossterm::style::SetAttribute(on))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Attributes {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "Attributes(")?;
+        let mut first = true;
+        for &(single, name, ..) in &Self::TABLE {
+            if self.contains(single) {
+                if !first {
+                    write!(fmtr, " | ")?;
+                }
+                write!(fmtr, "{}", name)?;
+                first = false;
+            }
+        }
+        if first {
+            write!(fmtr, "NONE")?;
+        }
+        write!(fmtr, ")")
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self::Output {
+        Self { bits: self.bits | other.bits }
+    }
+}
+
+impl BitOrAssign for Attributes {
+    fn bitor_assign(&mut self, other: Self) {
+        *self = *self | other;
+    }
+}
+
+impl BitAnd for Attributes {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self::Output {
+        Self { bits: self.bits & other.bits }
+    }
+}
+
+impl BitAndAssign for Attributes {
+    fn bitand_assign(&mut self, other: Self) {
+        *self = *self & other;
+    }
+}
+
+impl Sub for Attributes {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self { bits: self.bits & !other.bits }
+    }
+}
+
+impl SubAssign for Attributes {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Not for Attributes {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self::ALL - self
+    }
 }
 
 /// A function that updates a [`Tile`].
@@ -38,19 +297,22 @@ where
     }
 }
 
-/// Updates a [`Tile`] to set the foreground to the given character and color,
-/// but the color will be adapted to contrast the background.
+/// Updates a [`Tile`] to set the foreground to the given character, color and
+/// attributes, but the color will be adapted to contrast the background.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Foreground {
     /// The new displayed grapheme.
     pub grapheme: TermGrapheme,
     /// Foreground color to be contrasted with the background.
     pub color: Color,
+    /// The new text attributes.
+    pub attributes: Attributes,
 }
 
 impl Updater for Foreground {
     fn update(self, tile: &mut Tile) {
         tile.grapheme = self.grapheme;
+        tile.attributes = self.attributes;
         tile.colors.foreground = self
             .color
             .with_approx_brightness(!tile.colors.background.approx_brightness())
@@ -74,3 +336,17 @@ impl Updater for Background {
             .with_approx_brightness(!tile.colors.background.approx_brightness())
     }
 }
+
+/// Updates a [`Tile`] to set its text attributes, leaving grapheme and colors
+/// unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decoration {
+    /// The new text attributes.
+    pub attributes: Attributes,
+}
+
+impl Updater for Decoration {
+    fn update(self, tile: &mut Tile) {
+        tile.attributes = self.attributes;
+    }
+}
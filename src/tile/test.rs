@@ -0,0 +1,61 @@
+use crate::tile::{Attributes, Link};
+
+fn diff(prev: Attributes, next: Attributes) -> String {
+    let mut buf = String::new();
+    next.write_diff(prev, &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn set_operations() {
+    let attrs = Attributes::BOLD | Attributes::UNDERLINE;
+    assert!(attrs.contains(Attributes::BOLD));
+    assert!(!attrs.contains(Attributes::BOLD | Attributes::ITALIC));
+    assert!(attrs.intersects(Attributes::BOLD | Attributes::ITALIC));
+    assert_eq!(attrs - Attributes::BOLD, Attributes::UNDERLINE);
+    assert!((!Attributes::ALL).is_empty());
+    assert_eq!(
+        format!("{:?}", attrs),
+        "Attributes(BOLD | UNDERLINE)".to_owned()
+    );
+    assert_eq!(format!("{:?}", Attributes::NONE), "Attributes(NONE)");
+}
+
+#[test]
+fn diff_unchanged_is_empty() {
+    let attrs = Attributes::BOLD | Attributes::REVERSE;
+    assert_eq!(diff(attrs, attrs), "");
+}
+
+#[test]
+fn diff_only_changes() {
+    let prev = Attributes::BOLD;
+    let next = Attributes::BOLD | Attributes::ITALIC;
+    assert_eq!(diff(prev, next), "\x1b[3m");
+    assert_eq!(diff(next, prev), "\x1b[23m");
+}
+
+#[test]
+fn diff_intensity_restores_remaining() {
+    let prev = Attributes::BOLD | Attributes::DIM;
+    assert_eq!(diff(prev, Attributes::DIM), "\x1b[22m\x1b[2m");
+    assert_eq!(diff(prev, Attributes::NONE), "\x1b[22m");
+}
+
+#[test]
+fn links_are_interned() {
+    let link = Link::new("https://example.com/a");
+    assert_eq!(link, Link::new("https://example.com/a"));
+    assert_ne!(link, Link::new("https://example.com/b"));
+    assert_eq!(&*link.url(), "https://example.com/a");
+    assert_eq!(Link::new("https://example.com/\n\u{1b}a"), link);
+    assert!(link < Link::new("https://example.com/b"));
+
+    let mut buf = String::new();
+    Link::write_change(Some(&link), &mut buf).unwrap();
+    Link::write_change(None, &mut buf).unwrap();
+    assert_eq!(
+        buf,
+        "\u{1b}]8;;https://example.com/a\u{1b}\\\u{1b}]8;;\u{1b}\\"
+    );
+}
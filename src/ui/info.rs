@@ -101,7 +101,7 @@ impl InfoDialog {
 
     /// Renders the message of the dialog.
     fn render_message(&self, screen: &mut Screen) -> Coord {
-        screen.styled_text(&self.message, self.style)
+        screen.styled_text(&self.message, self.style.clone())
     }
 
     /// Renders the OK button.
@@ -50,7 +50,9 @@ where
     pub selected_colors: Color2,
     /// Unselected option colors.
     pub unselected_colors: Color2,
-    /// Input box's cursor colors.
+    /// Input box's cursor colors. Unused: the terminal's cursor is shown
+    /// instead.
+    #[deprecated(note = "the terminal's cursor is shown instead")]
     pub cursor_colors: Color2,
     /// Input box colors.
     pub box_colors: Color2,
@@ -72,6 +74,7 @@ where
 {
     /// Creates a new input dialog, with the given title, initial buffer,
     /// maximum input size, and filter function.
+    #[allow(deprecated)]
     pub fn new(
         title: TermString,
         buffer: TermString,
@@ -266,6 +269,27 @@ where
                         shift: false,
                     } => self.key_backspace(screen),
 
+                    KeyEvent {
+                        main_key: Key::Delete,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_delete(screen),
+
+                    KeyEvent {
+                        main_key: Key::Home,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_home(screen),
+
+                    KeyEvent {
+                        main_key: Key::End,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_end(screen),
+
                     #[cfg(feature = "clipboard")]
                     KeyEvent {
                         main_key: Key::Char('v'),
@@ -284,12 +308,17 @@ where
                     _ => (),
                 },
 
+                Some(Event::Paste(text)) if screen.valid_size() => {
+                    self.pasted(screen, &text)
+                },
+
                 Some(Event::Resize(evt)) => self.resized(evt, screen),
 
                 _ => (),
             }
         }
 
+        term.lock_now().await?.screen().set_cursor(None);
         Ok(())
     }
 
@@ -371,33 +400,74 @@ where
         }
     }
 
+    /// Should be triggered when DELETE key is pressed.
+    fn key_delete(&mut self, screen: &mut Screen) {
+        if self.cursor < self.buffer.len() {
+            self.buffer.remove(self.cursor);
+            self.render_input_box(screen);
+        }
+    }
+
+    /// Should be triggered when HOME key is pressed.
+    fn key_home(&mut self, screen: &mut Screen) {
+        if self.cursor > 0 {
+            self.cursor = 0;
+            self.render_input_box(screen);
+        }
+    }
+
+    /// Should be triggered when END key is pressed.
+    fn key_end(&mut self, screen: &mut Screen) {
+        if self.cursor < self.buffer.len() {
+            self.cursor = self.buffer.len();
+            self.render_input_box(screen);
+        }
+    }
+
     #[cfg(feature = "clipboard")]
     /// Should be triggered when Ctrl-Shift-V is pressed (paste).
     fn key_paste(&mut self, screen: &mut Screen) -> Result<(), Error> {
         let content = clipboard::get()?;
-        for ch in content.chars() {
-            self.insert(screen, ch);
-        }
+        self.insert(screen, content.chars());
         Ok(())
     }
 
     /// Should be triggered when generic character key is pressed.
     fn key_char(&mut self, screen: &mut Screen, ch: char) {
-        self.insert(screen, ch);
+        self.insert(screen, iter::once(ch));
+    }
+
+    /// Should be triggered when text is pasted through bracketed paste.
+    fn pasted(&mut self, screen: &mut Screen, text: &TermString) {
+        self.insert(screen, text.as_str().chars());
     }
 
-    fn insert(&mut self, screen: &mut Screen, ch: char) {
-        if (self.dialog.filter)(ch) {
-            let test_string = format!("a{}", ch);
-            if test_string.graphemes(true).count() > 1 {
-                let length = coord::from_index(self.buffer.len());
-                if length < self.actual_max {
+    /// Inserts the given characters at the cursor, skipping the ones rejected
+    /// by the filter, until the buffer is full. Renders only once.
+    fn insert<I>(&mut self, screen: &mut Screen, chars: I)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut changed = false;
+
+        for ch in chars {
+            let length = coord::from_index(self.buffer.len());
+            if length >= self.actual_max {
+                break;
+            }
+            if (self.dialog.filter)(ch) {
+                let test_string = format!("a{}", ch);
+                if test_string.graphemes(true).count() > 1 {
                     self.buffer.insert(self.cursor, ch);
                     self.cursor += 1;
-                    self.render_input_box(screen);
+                    changed = true;
                 }
             }
         }
+
+        if changed {
+            self.render_input_box(screen);
+        }
     }
 
     /// Should be triggered when the screen is resized.
@@ -441,28 +511,26 @@ where
             .top_margin(self.y_of_box())
             .colors(self.dialog.box_colors);
         let string = tstring![&field];
+        // The box is a single line, placed as `styled_text` places it.
+        let size = screen.size();
+        let slack = style
+            .make_size(size)
+            .x
+            .saturating_sub(coord::from_index(string.width()));
+        let box_x = slack * style.align_numer / style.align_denom;
         screen.styled_text(&string, style);
 
-        let width = screen.size().x;
-        let correction = coord::to_index(self.actual_max % 2 + width % 2 + 1);
-        let length = field.len() - correction % 2;
-
-        field.clear();
-        for i in 0 .. length + 1 {
-            if i == self.cursor {
-                field.push('¯')
-            } else {
-                field.push(' ')
-            }
+        // The terminal's cursor is shown at the insertion point.
+        let before = self.buffer[.. self.cursor].iter().collect::<String>();
+        let point = Vec2 {
+            x: box_x + coord::from_index(tstring![before].width()),
+            y: self.y_of_box(),
+        };
+        if point.x < size.x && point.y < size.y {
+            screen.set_cursor(Some(point));
+        } else {
+            screen.set_cursor(None);
         }
-
-        let style = Style::default()
-            .align(1, 2)
-            .top_margin(self.y_of_box() + 1)
-            .left_margin(1)
-            .colors(self.dialog.cursor_colors);
-        let string = tstring![&field];
-        screen.styled_text(&string, style);
     }
 
     /// Renders an item/option of the input dialog.
@@ -6,7 +6,15 @@ use crate::{
     coord,
     coord::{Coord, Vec2},
     error::Error,
-    event::{Event, Key, KeyEvent, ResizeEvent},
+    event::{
+        Event,
+        Key,
+        KeyEvent,
+        MouseButton,
+        MouseEvent,
+        MouseKind,
+        ResizeEvent,
+    },
     screen::Screen,
     string::{TermGrapheme, TermString},
     style::Style,
@@ -244,6 +252,34 @@ where
                         shift: false,
                     } => self.key_right(screen),
 
+                    KeyEvent {
+                        main_key: Key::Home,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_home(screen),
+
+                    KeyEvent {
+                        main_key: Key::End,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_end(screen),
+
+                    KeyEvent {
+                        main_key: Key::PageUp,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_page_up(screen),
+
+                    KeyEvent {
+                        main_key: Key::PageDown,
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    } => self.key_page_down(screen),
+
                     KeyEvent {
                         main_key: Key::Enter,
                         ctrl: false,
@@ -254,6 +290,13 @@ where
                     _ => (),
                 },
 
+                Some(Event::Mouse(evt)) if screen.valid_size() => {
+                    let finished = self.mouse(evt, screen);
+                    if finished {
+                        break;
+                    }
+                },
+
                 Some(Event::Resize(evt)) => self.resized(evt, screen),
 
                 _ => (),
@@ -318,6 +361,104 @@ where
         }
     }
 
+    /// Should be triggered when HOME key is pressed.
+    fn key_home(&mut self, screen: &mut Screen) {
+        if !self.menu.options.is_empty()
+            && (self.is_cancelling() || self.selected > 0)
+        {
+            if self.is_cancelling() {
+                self.cancel = Some(false);
+            }
+            self.move_to(0, screen);
+        }
+    }
+
+    /// Should be triggered when END key is pressed.
+    fn key_end(&mut self, screen: &mut Screen) {
+        let last = self.menu.options.len().saturating_sub(1);
+        if self.selected < last {
+            self.move_to(last, screen);
+        }
+    }
+
+    /// Should be triggered when PAGE UP key is pressed.
+    fn key_page_up(&mut self, screen: &mut Screen) {
+        let mut page = (self.last_row - self.first_row).max(1);
+        let cancelled = self.is_cancelling() && !self.menu.options.is_empty();
+        if cancelled {
+            // Leaving the cancel option counts as one step.
+            self.cancel = Some(false);
+            page -= 1;
+        }
+        let option = self.selected.saturating_sub(page);
+        if cancelled || option != self.selected {
+            self.move_to(option, screen);
+        }
+    }
+
+    /// Should be triggered when PAGE DOWN key is pressed.
+    fn key_page_down(&mut self, screen: &mut Screen) {
+        let page = (self.last_row - self.first_row).max(1);
+        let last = self.menu.options.len().saturating_sub(1);
+        let option = (self.selected + page).min(last);
+        if option > self.selected {
+            self.move_to(option, screen);
+        }
+    }
+
+    /// Selects the given option, scrolling just enough for it to be visible,
+    /// and renders the menu.
+    fn move_to(&mut self, option: usize, screen: &mut Screen) {
+        let visible = self.last_row - self.first_row;
+        self.selected = option;
+        if option < self.first_row {
+            self.first_row = option;
+        } else if option >= self.last_row {
+            self.first_row = option + 1 - visible;
+        }
+        self.update_last_row(screen.size());
+        self.render(screen);
+    }
+
+    /// Should be triggered when the mouse is used. Scrolling moves the
+    /// selection, clicking an option selects it. Returns whether the selection
+    /// is finished.
+    fn mouse(&mut self, evt: MouseEvent, screen: &mut Screen) -> bool {
+        match evt.kind {
+            MouseKind::ScrollUp => {
+                self.key_up(screen);
+                false
+            },
+
+            MouseKind::ScrollDown => {
+                self.key_down(screen);
+                false
+            },
+
+            MouseKind::Press(MouseButton::Left) => {
+                let screen_size = screen.size();
+                if self.cancel.is_some() && evt.pos.y + 2 == screen_size.y {
+                    self.cancel = Some(true);
+                    return true;
+                }
+                let mut range = self.range_of_screen(screen_size);
+                range.end = range.end.min(self.menu.options.len());
+                match range.find(|&i| self.y_of_option(i) == evt.pos.y) {
+                    Some(option) => {
+                        self.selected = option;
+                        if self.cancel.is_some() {
+                            self.cancel = Some(false);
+                        }
+                        true
+                    },
+                    None => false,
+                }
+            },
+
+            _ => false,
+        }
+    }
+
     /// Should be triggered when screen is resized.
     fn resized(&mut self, evt: ResizeEvent, screen: &mut Screen) {
         if let Some(size) = evt.size {
@@ -345,9 +486,13 @@ where
     /// Returns the index of the last visible option in the screen.
     fn screen_end(&self, screen_size: Vec2) -> usize {
         let cancel = if self.cancel.is_some() { 4 } else { 0 };
-        let mut available = screen_size.y - self.menu.title_y;
-        available -= 2 * (self.menu.pad_after_title - 1) + cancel;
-        let extra = available / (self.menu.pad_after_option + 1) - 2;
+        // Saturates, since small (e.g. inline) screens may fit no option.
+        let mut available = screen_size.y.saturating_sub(self.menu.title_y);
+        available = available.saturating_sub(
+            2 * self.menu.pad_after_title.saturating_sub(1) + cancel,
+        );
+        let extra =
+            (available / (self.menu.pad_after_option + 1)).saturating_sub(2);
         self.first_row + coord::to_index(extra)
     }
 
@@ -365,7 +510,7 @@ where
             Style::default().align(1, 2).colors(self.menu.arrow_colors);
 
         let mut range = self.range_of_screen(screen.size());
-        self.render_up_arrow(screen, arrow_style);
+        self.render_up_arrow(screen, arrow_style.clone());
         self.render_down_arrow(screen, arrow_style, &mut range);
 
         self.render_options(screen, range);